@@ -0,0 +1,147 @@
+use std::{io::{Read, Write}, path::{Path, PathBuf}};
+
+use flate2::{read::GzDecoder, write::GzEncoder, Compression};
+use serde::{Serialize, Deserialize};
+use sha2::{Digest, Sha256};
+
+use crate::{newspapers, model::{JournalNew, Newspaper}};
+
+/// Used by `--archive` when the config doesn't name a directory
+pub const ARCHIVE_DIR: &str = "jornais_archive";
+
+/// One line per archived fetch, next to the `objects` directory with the compressed pages
+const INDEX_FILE: &str = "index.jsonl";
+
+/// A listing page as it was fetched, its body is kept in the archive under `sha256`
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ArchiveEntry {
+    pub newspaper: Newspaper,
+    pub url: String,
+    pub fetched_at: String,
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    /// Hash of the undecoded body, the same page fetched twice is stored once
+    pub sha256: String,
+    pub bytes: usize
+}
+
+impl ArchiveEntry {
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers.iter().find(|(header, _)| header.eq_ignore_ascii_case(name)).map(|(_, value)| value.as_str())
+    }
+}
+
+fn object_path(directory: &Path, sha256: &str) -> PathBuf {
+    directory.join("objects").join(&sha256[..2]).join(format!("{sha256}.gz"))
+}
+
+/// Keeps the body of a fetch gzipped under its hash and appends its metadata to the index
+pub fn store(directory: &str, newspaper: &Newspaper, url: &str, status: u16, headers: &[(String, String)], raw: &[u8]) -> Result<ArchiveEntry, String> {
+    let directory = Path::new(directory);
+    let sha256 = hex::encode(Sha256::digest(raw));
+    let path = object_path(directory, &sha256);
+
+    if !path.exists() {
+        std::fs::create_dir_all(path.parent().expect("Objects are always inside a directory")).map_err(|error| error.to_string())?;
+
+        let mut encoder = GzEncoder::new(vec![], Compression::default());
+        encoder.write_all(raw).map_err(|error| error.to_string())?;
+        crate::files::write_atomically(&path, encoder.finish().map_err(|error| error.to_string())?).map_err(|error| error.to_string())?;
+    }
+
+    let entry = ArchiveEntry {
+        newspaper: newspaper.clone(),
+        url: String::from(url),
+        fetched_at: chrono::Utc::now().to_rfc3339(),
+        status,
+        headers: headers.to_vec(),
+        sha256,
+        bytes: raw.len()
+    };
+
+    let line = serde_json::to_string(&entry).expect("Archive entries should be serializable");
+
+    std::fs::OpenOptions::new()
+    .create(true)
+    .append(true)
+    .open(directory.join(INDEX_FILE))
+    .and_then(|mut index| writeln!(index, "{}", line))
+    .map_err(|error| error.to_string())?;
+
+    Ok(entry)
+}
+
+/// Archived fetches, oldest first, optionally only the ones of `source` fetched after `since`
+pub fn entries(directory: &str, source: Option<&Newspaper>, since: Option<chrono::DateTime<chrono::Utc>>) -> Vec<ArchiveEntry> {
+    let data = std::fs::read_to_string(Path::new(directory).join(INDEX_FILE)).unwrap_or_default();
+
+    data
+    .lines()
+    .filter_map(|line| serde_json::from_str::<ArchiveEntry>(line).ok())
+    .filter(|entry| source.is_none_or(|source| &entry.newspaper == source))
+    .filter(|entry| since.is_none_or(|since| {
+        chrono::DateTime::parse_from_rfc3339(&entry.fetched_at).is_ok_and(|fetched_at| fetched_at >= since)
+    }))
+    .collect()
+}
+
+/// The body of an archived fetch decoded the same way a live one is, checking it against its hash
+pub fn read(directory: &str, entry: &ArchiveEntry) -> Result<String, String> {
+    let compressed = std::fs::read(object_path(Path::new(directory), &entry.sha256)).map_err(|error| error.to_string())?;
+    let mut raw = vec![];
+    GzDecoder::new(compressed.as_slice()).read_to_end(&mut raw).map_err(|error| error.to_string())?;
+
+    if hex::encode(Sha256::digest(&raw)) != entry.sha256 {
+        return Err(format!("the archived page {} is corrupted", entry.sha256))
+    }
+
+    Ok(crate::text::decode_html(&raw, entry.header("content-type")))
+}
+
+/// Runs the current scraper of the source over an archived fetch, a scraper that panics gives an error like in a live fetch
+pub fn reparse(directory: &str, entry: &ArchiveEntry) -> Result<Vec<JournalNew>, String> {
+    let html = read(directory, entry)?;
+
+    std::panic::catch_unwind(|| newspapers::parse_news(&entry.newspaper, &html).news).map_err(|panic| {
+        panic.downcast_ref::<&str>().map(|message| String::from(*message))
+        .or(panic.downcast_ref::<String>().cloned())
+        .unwrap_or(String::from("the scraper panicked"))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn archived_listings_can_be_reparsed() {
+        let directory = std::env::temp_dir().join(format!("jornais_archive_test_{}", std::process::id()));
+        let directory = directory.to_str().unwrap();
+        let _ = std::fs::remove_dir_all(directory);
+
+        let html = include_str!("../fixtures/tn.html");
+        let headers = vec![(String::from("content-type"), String::from("text/html; charset=utf-8"))];
+        let first = store(directory, &Newspaper::TN, "https://tn.com.ar/ultimas-noticias/", 200, &headers, html.as_bytes()).unwrap();
+        let second = store(directory, &Newspaper::TN, "https://tn.com.ar/ultimas-noticias/", 200, &headers, html.as_bytes()).unwrap();
+
+        // The same page is stored once however many times it is fetched
+        assert_eq!(first.sha256, second.sha256);
+        assert_eq!(std::fs::read_dir(Path::new(directory).join("objects").join(&first.sha256[..2])).unwrap().count(), 1);
+
+        let latin1 = store(directory, &Newspaper::LAVOZ, "https://www.lavoz.com.ar/ultimas-noticias/", 200, &[
+            (String::from("Content-Type"), String::from("text/html; charset=ISO-8859-1"))
+        ], b"<p>Econom\xeda</p>").unwrap();
+
+        assert_eq!(entries(directory, None, None), [first.clone(), second, latin1.clone()]);
+        assert_eq!(entries(directory, Some(&Newspaper::LAVOZ), None), std::slice::from_ref(&latin1));
+        assert_eq!(read(directory, &latin1).unwrap(), "<p>Economía</p>");
+
+        assert_eq!(reparse(directory, &first).unwrap(), newspapers::parse_tn(html).news);
+        assert!(reparse(directory, &latin1).unwrap().is_empty());
+
+        std::fs::write(object_path(Path::new(directory), &latin1.sha256), b"not gzip").unwrap();
+        assert!(read(directory, &latin1).is_err());
+
+        let _ = std::fs::remove_dir_all(directory);
+    }
+}
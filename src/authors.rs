@@ -0,0 +1,169 @@
+use std::collections::HashMap;
+
+use crate::{model::Newspaper, text::{clean, slug}};
+
+/// Words before the names in a byline, like `Por Juan Pérez`
+const BYLINE_PREFIXES: [&str; 4] = ["escrito por", "por", "by", "texto"];
+
+/// What goes between the names of a byline with several authors
+const NAME_SEPARATORS: [&str; 8] = [",", ";", "/", "|", "&", " y ", " e ", " and "];
+
+/// Words kept in lowercase when a name is title-cased, unless they start it
+const NAME_PARTICLES: [&str; 8] = ["de", "del", "la", "las", "los", "y", "van", "von"];
+
+/// Bylines longer than this are most likely a summary or a caption rather than a name
+const MAX_NAME_WORDS: usize = 5;
+
+/// Identifies an author across spellings, ignoring case, accents and spacing, so `JUAN PEREZ` and `Juan Pérez` are the same
+pub fn key(name: &str) -> String {
+    slug(name)
+}
+
+fn title_case(word: &str) -> String {
+    let mut characters = word.chars();
+
+    match characters.next() {
+        Some(first) => first.to_uppercase().chain(characters.flat_map(|character| character.to_lowercase())).collect(),
+        None => String::new()
+    }
+}
+
+/// Cleans a name, title-casing it when it is written all in uppercase or all in lowercase
+fn normalize_name(name: &str) -> String {
+    let name = clean(name.trim_matches(|c: char| c.is_whitespace() || ".:-–—".contains(c)));
+
+    if name.chars().any(char::is_lowercase) && name.chars().any(char::is_uppercase) {
+        return name
+    }
+
+    name
+    .split(' ')
+    .enumerate()
+    .map(|(index, word)| match index > 0 && NAME_PARTICLES.contains(&word.to_lowercase().as_str()) {
+        true => word.to_lowercase(),
+        false => title_case(word)
+    })
+    .collect::<Vec<String>>()
+    .join(" ")
+}
+
+fn strip_prefix(byline: &str) -> &str {
+    let lowercase = byline.to_lowercase();
+
+    BYLINE_PREFIXES
+    .iter()
+    .find(|prefix| lowercase.starts_with(*prefix) && lowercase[prefix.len()..].starts_with([' ', ':']))
+    .and_then(|prefix| byline.get(prefix.len()..))
+    .map(|names| names.trim_start_matches([' ', ':']))
+    .unwrap_or(byline)
+}
+
+/// The authors named in the bylines of a story, like `Por Juan Pérez y María Gómez`,
+/// without the prefixes, one per name and each one only once
+pub fn parse(bylines: &[String]) -> Vec<String> {
+    let mut authors: Vec<String> = vec![];
+
+    for byline in bylines {
+        let byline = clean(byline);
+        let mut names = String::from(strip_prefix(&byline));
+
+        for separator in NAME_SEPARATORS {
+            names = names.replace(separator, "\n");
+        }
+
+        for name in names.lines().map(normalize_name) {
+            let plausible = !name.is_empty()
+            && name.split(' ').count() <= MAX_NAME_WORDS
+            && !name.chars().any(|c| c.is_ascii_digit());
+
+            if plausible && !authors.iter().any(|author| key(author) == key(&name)) {
+                authors.push(name);
+            }
+        }
+    }
+
+    authors
+}
+
+/// Whether one of `authors` is one of the `wanted` ones, compared by `key`
+pub fn signed_by(authors: &[String], wanted: &[String]) -> bool {
+    authors.iter().any(|author| wanted.iter().any(|wanted| key(author) == key(wanted)))
+}
+
+/// Authors with the number of stories they signed
+pub type AuthorCounts = Vec<(String, usize)>;
+
+/// How many stories each author signed in each source, most prolific first with at most `limit` per source.
+/// The spellings of an author count together and are shown with the most used one.
+pub fn most_prolific(stories: &[(Newspaper, Vec<String>)], limit: usize) -> Vec<(Newspaper, AuthorCounts)> {
+    let mut spellings: HashMap<(Newspaper, String), HashMap<String, usize>> = HashMap::new();
+
+    for (newspaper, authors) in stories {
+        for author in authors {
+            *spellings
+            .entry((newspaper.clone(), key(author)))
+            .or_default()
+            .entry(author.clone())
+            .or_default() += 1;
+        }
+    }
+
+    let mut per_source: Vec<(Newspaper, AuthorCounts)> = vec![];
+
+    for ((newspaper, _), spellings) in spellings {
+        let count = spellings.values().sum();
+        let name = spellings
+        .into_iter()
+        .max_by(|a, b| a.1.cmp(&b.1).then(b.0.cmp(&a.0)))
+        .map(|(name, _)| name)
+        .unwrap_or_default();
+
+        match per_source.iter_mut().find(|(source, _)| *source == newspaper) {
+            Some((_, authors)) => authors.push((name, count)),
+            None => per_source.push((newspaper, vec![(name, count)]))
+        }
+    }
+
+    for (_, authors) in per_source.iter_mut() {
+        authors.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+        authors.truncate(limit);
+    }
+
+    per_source.sort_by_key(|(newspaper, _)| newspaper.to_string());
+
+    per_source
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bylines_become_normalized_authors() {
+        assert_eq!(parse(&[String::from("Por JUAN PÉREZ y María Gómez | 12 de marzo")]), ["Juan Pérez", "María Gómez"]);
+        assert_eq!(parse(&[String::from("Escrito por: ana de la vega, Redacción")]), ["Ana de la Vega", "Redacción"]);
+        assert_eq!(parse(&[String::from("Juan Pérez"), String::from("juan perez")]), ["Juan Pérez"]);
+        assert!(parse(&[String::from("Una nota demasiado larga para ser el nombre de alguien")]).is_empty());
+    }
+
+    #[test]
+    fn authors_match_in_any_spelling() {
+        assert!(signed_by(&[String::from("Juan Pérez")], &[String::from("juan perez")]));
+        assert!(!signed_by(&[String::from("Juan Pérez")], &[String::from("María Gómez")]));
+    }
+
+    #[test]
+    fn spellings_count_together() {
+        let stories = [
+            (Newspaper::PAGINA12, vec![String::from("Juan Pérez"), String::from("María Gómez")]),
+            (Newspaper::PAGINA12, vec![String::from("Juan Perez")]),
+            (Newspaper::PAGINA12, vec![String::from("Juan Perez")]),
+            (Newspaper::CLARIN, vec![String::from("Ana de la Vega")])
+        ];
+
+        assert_eq!(most_prolific(&stories, 1), [
+            (Newspaper::CLARIN, vec![(String::from("Ana de la Vega"), 1)]),
+            (Newspaper::PAGINA12, vec![(String::from("Juan Perez"), 3)])
+        ]);
+    }
+}
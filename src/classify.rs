@@ -0,0 +1,225 @@
+use serde::{Serialize, Deserialize};
+use crate::{authors, text::slug, model::{ContentKind, JournalNew, Section}};
+
+/// Each kind with the markers of its class names or `data-` attributes and the whole words that mark it
+/// in the path of the link, checked in this order
+const KIND_MARKERS: [(ContentKind, &[&str], &[&str]); 4] = [
+    (ContentKind::SPONSORED, &["sponsor", "sponsored", "patrocinado", "patrocinio", "brand-content", "branded", "brandstudio", "publinota", "advertorial", "anunciante"], &["sponsored", "patrocinado", "brandstudio", "publinota"]),
+    (ContentKind::LIVE, &["live", "liveblog", "envivo", "en-vivo", "vivo", "minutoaminuto", "minuto-a-minuto"], &["live", "envivo", "minutoaminuto"]),
+    (ContentKind::VIDEO, &["video", "videos"], &["video", "videos"]),
+    (ContentKind::GALLERY, &["galeria", "gallery", "fotogaleria", "slideshow"], &["galeria", "gallery", "fotogaleria", "fotos"])
+];
+
+const PAYWALL_MARKERS: [&str; 9] = ["premium", "suscriptor", "suscriptores", "subscriber", "subscribers", "paywall", "paywalled", "candado", "locked"];
+
+/// Lowercase words of `text`, splitting on anything that isn't a letter or a digit
+fn words(text: &str) -> Vec<String> {
+    text
+    .split(|c: char| !c.is_alphanumeric())
+    .filter(|word| !word.is_empty())
+    .map(|word| word.to_lowercase())
+    .collect()
+}
+
+/// Whether a class name or attribute token has `marker` as a whole part, like `premium` in `card--premium`
+/// or `brand-content` in `brand-content`, but not `live` in `liverpool`
+fn has_marker(token: &str, marker: &str) -> bool {
+    format!("-{}-", token.replace('_', "-")).contains(&format!("-{marker}-"))
+}
+
+/// The kind of a story and whether it is paywalled, from the class names and `data-` attributes of its
+/// listing markup together with the path of its link, like `/videos/...` or `/en-vivo/...`
+pub fn classify(markers: &[String], link: &str) -> (ContentKind, bool) {
+    let tokens: Vec<String> = markers.iter().flat_map(|marker| marker.split_whitespace()).map(|token| token.to_lowercase()).collect();
+    let marked = |candidates: &[&str]| tokens.iter().any(|token| candidates.iter().any(|candidate| has_marker(token, candidate)));
+    let path = reqwest::Url::parse(link).map(|link| link.path().to_lowercase()).unwrap_or_default();
+    let path_words = words(&path.replace("en-vivo", "envivo").replace("minuto-a-minuto", "minutoaminuto"));
+
+    let kind = KIND_MARKERS
+    .iter()
+    .find(|(_, markers, path_markers)| {
+        marked(markers) || path_words.iter().any(|word| path_markers.contains(&word.as_str()))
+    })
+    .map(|(kind, _, _)| *kind)
+    .unwrap_or_default();
+
+    let paywalled = marked(&PAYWALL_MARKERS);
+
+    (kind, paywalled)
+}
+
+/// Slugs the sources use for their sections, in URL paths, breadcrumbs or labels, and where they go in the taxonomy
+const SECTION_SLUGS: [(Section, &[&str]); 11] = [
+    (Section::POLITICS, &["politica", "el-pais", "elecciones", "congreso", "gobierno"]),
+    (Section::ECONOMY, &["economia", "finanzas", "negocios", "dolar", "mercados", "campo", "agro", "energia"]),
+    (Section::SPORTS, &["deportes", "deportivo", "futbol", "tenis", "rugby", "basquet", "automovilismo"]),
+    (Section::POLICE, &["policiales", "policial", "seguridad", "sucesos", "judiciales"]),
+    (Section::SOCIETY, &["sociedad", "ciudad", "ciudades", "ciudadanos", "informacion-general", "la-ciudad", "educacion"]),
+    (Section::WORLD, &["mundo", "el-mundo", "internacional", "internacionales", "america", "america-latina"]),
+    (Section::ENTERTAINMENT, &["espectaculos", "show", "famosos", "teleshow", "television", "tv"]),
+    (Section::CULTURE, &["cultura", "libros", "arte", "musica", "cine"]),
+    (Section::TECHNOLOGY, &["tecnologia", "tecno", "ciencia", "ciencia-y-tecnologia"]),
+    (Section::HEALTH, &["salud", "bienestar"]),
+    (Section::OPINION, &["opinion", "editorial", "columnistas"])
+];
+
+fn section_of_slug(slug: &str) -> Option<Section> {
+    SECTION_SLUGS
+    .iter()
+    .find(|(_, slugs)| slugs.contains(&slug))
+    .map(|(section, _)| *section)
+}
+
+/// The section of a story from the labels of its listing markup, like breadcrumbs or `data-section` values,
+/// falling back to the first segment of the path of its link that names a known section
+pub fn section(labels: &[String], link: &str) -> Section {
+    let from_labels = labels
+    .iter()
+    .flat_map(|label| label.split(['>', '/', '|', '›', '»']))
+    .find_map(|part| section_of_slug(&slug(part)));
+
+    let from_path = || reqwest::Url::parse(link).ok().and_then(|link| {
+        link.path_segments()?.find_map(|segment| section_of_slug(&slug(segment)))
+    });
+
+    from_labels.or_else(from_path).unwrap_or_default()
+}
+
+/// Which stories are monitored, shown and served
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(default)]
+pub struct NewsFilter {
+    /// Only keep these kinds, every kind but sponsored when empty
+    pub kinds: Vec<ContentKind>,
+    /// Only keep the paywalled stories when `true` or the free ones when `false`
+    pub paywalled: Option<bool>,
+    /// Only keep these sections, every section when empty
+    pub sections: Vec<Section>,
+    /// Only keep the stories signed by one of these authors, in any spelling, every story when empty
+    pub authors: Vec<String>
+}
+
+impl NewsFilter {
+    /// The kinds that are kept, every kind but sponsored when `kinds` is empty
+    pub fn allowed_kinds(&self) -> Vec<ContentKind> {
+        match self.kinds.is_empty() {
+            true => ContentKind::ALL.into_iter().filter(|kind| *kind != ContentKind::SPONSORED).collect(),
+            false => self.kinds.clone()
+        }
+    }
+
+    pub fn allows(&self, kind: ContentKind, paywalled: bool, section: Section, authors: &[String]) -> bool {
+        self.allowed_kinds().contains(&kind)
+        && self.paywalled.is_none_or(|wanted| wanted == paywalled)
+        && (self.sections.is_empty() || self.sections.contains(&section))
+        && (self.authors.is_empty() || authors::signed_by(authors, &self.authors))
+    }
+
+    pub fn matches(&self, journal_new: &JournalNew) -> bool {
+        self.allows(journal_new.kind, journal_new.paywalled, journal_new.section, &journal_new.authors)
+    }
+
+    /// Parses a list like `live,video`, empty for every kind but sponsored
+    pub fn parse_kinds(kinds: &str) -> Result<Vec<ContentKind>, String> {
+        kinds
+        .split(',')
+        .filter(|kind| !kind.trim().is_empty())
+        .map(|kind| kind.parse())
+        .collect()
+    }
+
+    /// Parses a list like `politics,economy`, empty for every section
+    pub fn parse_sections(sections: &str) -> Result<Vec<Section>, String> {
+        sections
+        .split(',')
+        .filter(|section| !section.trim().is_empty())
+        .map(|section| section.parse())
+        .collect()
+    }
+
+    /// Parses a list like `Juan Pérez,María Gómez`, empty for every author
+    pub fn parse_authors(authors: &str) -> Vec<String> {
+        authors
+        .split(',')
+        .map(|author| String::from(author.trim()))
+        .filter(|author| !author.is_empty())
+        .collect()
+    }
+
+    /// Parses `yes`, `no`, `true` or `false`
+    pub fn parse_paywalled(paywalled: &str) -> Result<bool, String> {
+        match paywalled.trim().to_lowercase().as_str() {
+            "yes" | "true" => Ok(true),
+            "no" | "false" => Ok(false),
+            _ => Err(format!("Unknown paywalled value {paywalled}, use yes or no"))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn markers(markers: &[&str]) -> Vec<String> {
+        markers.iter().map(|marker| String::from(*marker)).collect()
+    }
+
+    #[test]
+    fn stories_are_classified_from_their_markers_and_link() {
+        let page = "https://www.pagina12.com.ar";
+
+        assert_eq!(classify(&markers(&["article-item"]), &format!("{page}/1-nota")), (ContentKind::ARTICLE, false));
+        assert_eq!(classify(&markers(&["article-item article-item--premium", "icon-candado"]), &format!("{page}/2-nota")), (ContentKind::ARTICLE, true));
+        assert_eq!(classify(&markers(&["article-item", "data-live true"]), &format!("{page}/3-nota")), (ContentKind::LIVE, false));
+        assert_eq!(classify(&markers(&["article-item"]), &format!("{page}/videos/4-nota")), (ContentKind::VIDEO, false));
+        assert_eq!(classify(&markers(&["article-item brand-content"]), &format!("{page}/5-nota")), (ContentKind::SPONSORED, false));
+        assert_eq!(classify(&markers(&["article-item"]), &format!("{page}/6-liverpool-gano")), (ContentKind::ARTICLE, false));
+        assert_eq!(classify(&[], &format!("{page}/en-vivo/7-nota")), (ContentKind::LIVE, false));
+    }
+
+    #[test]
+    fn markers_are_whole_class_tokens() {
+        let page = "https://www.pagina12.com.ar/1-nota";
+
+        assert_eq!(classify(&markers(&["card card--branding"]), page), (ContentKind::ARTICLE, false));
+        assert_eq!(classify(&markers(&["team-liverpool"]), page), (ContentKind::ARTICLE, false));
+        assert_eq!(classify(&markers(&["block headlines"]), page), (ContentKind::ARTICLE, false));
+        assert_eq!(classify(&markers(&["nota-exclusiva"]), page), (ContentKind::ARTICLE, false));
+        assert_eq!(classify(&markers(&["card_live"]), page), (ContentKind::LIVE, false));
+        assert_eq!(classify(&markers(&["is-locked"]), page), (ContentKind::ARTICLE, true));
+    }
+
+    #[test]
+    fn stories_get_a_section_of_the_taxonomy() {
+        let page = "https://www.pagina12.com.ar";
+
+        assert_eq!(section(&markers(&["Inicio › Información General"]), &format!("{page}/1-nota")), Section::SOCIETY);
+        assert_eq!(section(&markers(&["Economía"]), &format!("{page}/politica/2-nota")), Section::ECONOMY);
+        assert_eq!(section(&[], &format!("{page}/secciones/el-mundo/3-nota")), Section::WORLD);
+        assert_eq!(section(&[], &format!("{page}/ultimas-noticias/5-nota")), Section::OTHER);
+    }
+
+    #[test]
+    fn filters_kinds_paywall_sections_and_authors() {
+        assert!(NewsFilter::default().allows(ContentKind::ARTICLE, true, Section::OTHER, &[]));
+        assert!(!NewsFilter::default().allows(ContentKind::SPONSORED, false, Section::OTHER, &[]));
+
+        let filter = NewsFilter {
+            kinds: NewsFilter::parse_kinds("article,sponsored").unwrap(),
+            paywalled: Some(NewsFilter::parse_paywalled("no").unwrap()),
+            sections: NewsFilter::parse_sections("economy,police").unwrap(),
+            authors: NewsFilter::parse_authors("Juan Pérez, ")
+        };
+        let juan = [String::from("JUAN PEREZ")];
+
+        assert!(filter.allows(ContentKind::SPONSORED, false, Section::ECONOMY, &juan));
+        assert!(!filter.allows(ContentKind::LIVE, false, Section::ECONOMY, &juan));
+        assert!(!filter.allows(ContentKind::ARTICLE, true, Section::ECONOMY, &juan));
+        assert!(!filter.allows(ContentKind::ARTICLE, false, Section::POLITICS, &juan));
+        assert!(!filter.allows(ContentKind::ARTICLE, false, Section::POLICE, &[String::from("María Gómez")]));
+
+        assert!(NewsFilter::parse_kinds("article,podcast").is_err());
+        assert!(NewsFilter::parse_paywalled("maybe").is_err());
+        assert!(NewsFilter::parse_sections("weather").is_err());
+    }
+}
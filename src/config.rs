@@ -0,0 +1,68 @@
+use serde::{Serialize, Deserialize};
+
+use crate::{classify::NewsFilter, images::ImageConfig, webhook::WebhookConfig, http::HttpConfig, logging::LogFormat, model::{Newspaper, Region}};
+
+pub const CONFIG_FILE: &str = "jornais.json";
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct Config {
+    /// Name used to keep the read and starred flags apart, defaults to the system user
+    pub user: String,
+    /// When set the HTML mode also serves the dashboard and its API on this address, like `127.0.0.1:8080`
+    pub server_address: Option<String>,
+    /// When set the Prometheus metrics are served on `/metrics` at this address, in both modes
+    pub metrics_address: Option<String>,
+    /// Only fetch and show the newspapers of these regions, every region when empty
+    pub regions: Vec<Region>,
+    /// Stories to monitor, every kind but sponsored of every section by default,
+    /// can be overridden with `--kind`, `--section`, `--paywalled` and `--author`
+    pub filter: NewsFilter,
+    /// Sources whose home page is also captured every cycle as a front page snapshot, none when empty,
+    /// can be overridden with `--front-pages clarin,tn` or `--front-pages all`
+    pub front_pages: Vec<Newspaper>,
+    pub webhooks: WebhookConfig,
+    /// Whether the lead images are cached and resized locally for the dashboard
+    pub images: ImageConfig,
+    /// User agent, timeouts, retries and proxy of the requests to the newspapers
+    pub http: HttpConfig,
+    /// `pretty` or `json`, can be overridden with `--log-format`
+    pub log_format: LogFormat
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            user: std::env::var("USER").unwrap_or(String::from("default")),
+            server_address: None,
+            metrics_address: None,
+            regions: vec![],
+            filter: NewsFilter::default(),
+            front_pages: vec![],
+            webhooks: WebhookConfig::default(),
+            images: ImageConfig::default(),
+            http: HttpConfig::default(),
+            log_format: LogFormat::default()
+        }
+    }
+}
+
+impl Config {
+    /// Loads the settings from `path`, falling back to the defaults when the file doesn't exist.
+    pub fn load(path: &str) -> Config {
+        Config::try_load(path).unwrap_or_else(|error| panic!("{error}"))
+    }
+
+    /// Like `load` but returns an error instead of panicking when the file can't be read or parsed
+    pub fn try_load(path: &str) -> Result<Config, String> {
+        let config_fp = std::path::Path::new(path);
+
+        if !config_fp.exists() {
+            return Ok(Config::default())
+        }
+
+        let data = std::fs::read_to_string(config_fp).map_err(|error| format!("Error retrieving file contents of the config file: {error}"))?;
+
+        serde_json::from_str(&data).map_err(|error| format!("Error parsing the config file: {error}"))
+    }
+}
@@ -0,0 +1,454 @@
+use jornais::{images, state::canonical_link, health::{HealthStatus, SourceHealth}, model::{ContentKind, JournalNew, Newspaper, Region, Section}};
+
+/// Escapes the scraped text that goes inside the markup, the scrapers decode the entities of the sources
+fn escape_html(text: &str) -> String {
+    text
+    .replace('&', "&amp;")
+    .replace('<', "&lt;")
+    .replace('>', "&gt;")
+    .replace('"', "&quot;")
+    .replace('\'', "&#39;")
+}
+
+fn format_news_to_html(title: String, journal_news: Vec<JournalNew>, newspaper: Newspaper) -> String {
+
+    let source_info = newspaper.info();
+
+    let mut news_html = format!(r#"
+        <h1 class="{}-title" data-region="{}"><a target="_blank" href="{}">{}</a></h1>
+    "#, source_info.id, source_info.region, escape_html(&source_info.home_url), escape_html(&title));
+
+    for news in journal_news {
+        let mut badges = String::from("");
+
+        if news.section != Section::OTHER {
+            badges += &format!(r#"<span class="badge section">{}</span>"#, escape_html(news.section.name()));
+        }
+
+        if news.kind != ContentKind::ARTICLE {
+            badges += &format!(r#"<span class="badge {}">{}</span>"#, escape_html(&news.kind.to_string()), escape_html(news.kind.name()));
+        }
+
+        if news.paywalled {
+            badges += r#"<span class="badge paywalled">Suscriptores</span>"#;
+        }
+
+        // Cached thumbnails are relative to the dashboard so they work both served and opened as a file
+        let image = match &news.image {
+            Some(image) => format!(
+                r#"<img class="thumbnail" loading="lazy" src="{}" alt="{}"{}{}>"#,
+                escape_html(&image.thumbnail.as_ref().map(|thumbnail| format!("{}/{thumbnail}", images::THUMBNAILS_DIR)).unwrap_or(image.url.clone())),
+                escape_html(&image.alt.clone().unwrap_or_default()),
+                image.width.map(|width| format!(r#" width="{width}""#)).unwrap_or_default(),
+                image.height.map(|height| format!(r#" height="{height}""#)).unwrap_or_default()
+            ),
+            None => String::from("")
+        };
+
+        let byline = match news.authors.is_empty() {
+            true => String::from(""),
+            false => format!(r#"<p class="byline">Por {}</p>"#, escape_html(&news.authors.join(", ")))
+        };
+
+        let formatted = format!(r#"
+        <div class="news {}" data-link="{}" data-region="{}" data-kind="{}" data-paywalled="{}" data-section="{}">
+            <button class="hideButton">v</button>
+            <button class="starButton">☆</button>
+            <div class="badges">{badges}</div>
+            {image}
+            <a target="_blank" href="{}">
+                <h3>{}</h3>
+            </a>
+            {byline}
+            <b>{}</b>
+        </div>
+        "#,
+        news.newspaper,
+        escape_html(&canonical_link(&news.key())),
+        news.newspaper.region(),
+        news.kind,
+        news.paywalled,
+        news.section,
+        escape_html(&news.link.clone().unwrap_or(String::from(""))),
+        escape_html(&news.title),
+        escape_html(&news.text),
+        );
+
+        news_html += formatted.as_str();
+    }
+
+    news_html
+
+}
+
+/// Builds the whole dashboard page with a section, a checkbox and a counter per newspaper
+pub fn format_dashboard(sources: Vec<(Newspaper, Vec<JournalNew>)>, source_health: &[SourceHealth]) -> String {
+    let toolbar: String = sources.iter().map(|(newspaper, _)| {
+        let (status, reasons) = match source_health.iter().find(|source| source.newspaper == *newspaper) {
+            Some(source) => (source.status, source.reasons.join(", ")),
+            None => (HealthStatus::HEALTHY, String::from(""))
+        };
+
+        format!(r#"
+            <div>
+                <input checked class="newspaper-checkbox {newspaper}-checkbox" data-newspaper="{newspaper}" type="checkbox">
+                <span>{}</span>
+                <span class="health {status}" title="{status} {}">●</span>
+            </div>"#,
+            escape_html(&newspaper.info().name),
+            escape_html(&reasons)
+        )
+    }).collect();
+
+    let regions: String = Region::ALL
+    .iter()
+    .filter(|region| sources.iter().any(|(newspaper, _)| newspaper.region() == **region))
+    .map(|region| format!(r#"<option value="{region}">{}</option>"#, region.name()))
+    .collect();
+
+    let kinds: String = ContentKind::ALL
+    .iter()
+    .filter(|kind| sources.iter().any(|(_, news)| news.iter().any(|journal_new| journal_new.kind == **kind)))
+    .map(|kind| format!(r#"<option value="{kind}">{}</option>"#, kind.name()))
+    .collect();
+
+    let sections: String = Section::ALL
+    .iter()
+    .filter(|section| sources.iter().any(|(_, news)| news.iter().any(|journal_new| journal_new.section == **section)))
+    .map(|section| format!(r#"<option value="{section}">{}</option>"#, section.name()))
+    .collect();
+
+    let toolbar = format!(r#"{toolbar}
+            <div>
+                <select id="region-filter">
+                    <option value="">Todas las regiones</option>
+                    {regions}
+                </select>
+                <select id="section-filter">
+                    <option value="">Todas las secciones</option>
+                    {sections}
+                </select>
+                <select id="kind-filter">
+                    <option value="">Todos los tipos</option>
+                    {kinds}
+                </select>
+                <select id="paywalled-filter">
+                    <option value="">Libres y para suscriptores</option>
+                    <option value="false">Solo libres</option>
+                    <option value="true">Solo para suscriptores</option>
+                </select>
+            </div>"#);
+
+    let counters = sources.iter().map(|(newspaper, _)| format!(
+        r#"<span>{}: <span class="{newspaper}Counter">0</span> noticias</span>"#,
+        escape_html(&newspaper.info().name)
+    )).collect::<Vec<String>>().join("-\n            ");
+
+    let news_html: String = sources.into_iter().map(|(newspaper, journal_news)| {
+        format_news_to_html(newspaper.info().name, journal_news, newspaper)
+    }).collect();
+
+    let styles = r#"
+    <style>
+        body {
+            display: flex;
+            flex-direction: column;
+            align-items: center;
+            background-color: rgba(44, 74, 83, 0.74);
+            color: rgb(0, 0, 0);
+            text-align: center;
+        }
+
+        .news {
+            display: flex;
+            flex-direction: column;
+            align-items: center;
+            width: 75%;
+            background-color: rgba(196, 188, 177, 0.288);
+            padding: 10px;
+            border-radius: 5px;
+            border: 2px solid rgba(247, 203, 121, 0.363);
+            margin-bottom: 10px;
+        }
+
+        .news > b {
+            font-size: 12px;
+            text-align: justify;
+        }
+        
+        h3 {
+            font-size: 24px;
+        }
+
+        h1 {
+            color: rgb(0, 0, 0);
+        }
+
+        h1 a {
+            color: rgb(0, 0, 0);
+        }
+
+        .thumbnail {
+            max-width: 320px;
+            width: 100%;
+            height: auto;
+            border-radius: 5px;
+        }
+
+        .badges {
+            display: flex;
+            gap: 5px;
+        }
+
+        .badge {
+            font-size: 11px;
+            padding: 2px 6px;
+            border-radius: 5px;
+            background-color: rgba(2, 51, 92, 0.2);
+        }
+
+        .byline {
+            margin: 0;
+            font-size: 12px;
+            color: rgb(90, 90, 90);
+        }
+
+        .badge.paywalled {
+            background-color: rgba(230, 145, 0, 0.4);
+        }
+
+        .health.healthy {
+            color: rgb(46, 125, 50);
+        }
+
+        .health.degraded {
+            color: rgb(230, 145, 0);
+        }
+
+        .health.broken {
+            color: rgb(198, 40, 40);
+        }
+
+        .news.read h3 {
+            opacity: 0.6;
+        }
+
+        .starButton {
+            display: none;
+            align-self: flex-end;
+            font-size: 16px;
+            background: none;
+            border: none;
+            cursor: pointer;
+        }
+
+        .served .starButton {
+            display: block;
+        }
+
+        .hideButton {
+            align-self: flex-start;
+            font-size: 16px;
+            background-color: rgba(255, 191, 52, 0.699);
+            border: none;
+            border-radius: 5px;
+            padding: 5px 10px
+        }
+
+        a {
+            text-decoration: none;
+            color: rgb(2, 51, 92);
+        }
+
+        a:hover {
+            text-decoration: underline;
+        }
+
+        #counters {
+            display: flex;
+            gap: 15px;
+            font-size: 15px;
+            background-color: rgba(196, 188, 177, 0.288);
+            border-radius: 5px;
+            padding: 10px
+        }
+
+        #toolbar {
+            position: sticky;
+            top: 0px;
+            right: 0px;
+            background-color: rgba(196, 188, 177, 0.288);
+            margin-bottom: 10px;
+            width: 100%;
+            border-radius: 5px;
+            display: flex;
+            align-items: center;
+            justify-content: space-evenly;
+            padding: 10px 0px;
+        }
+        
+    </style>
+    "#;
+
+    let script = r#"
+    <script defer>
+        const $ = (selector, searchIn) => searchIn ? searchIn.querySelectorAll(selector) : document.querySelectorAll(selector)
+        const $1 = (selector, searchIn) => searchIn ? searchIn.querySelector(selector) : document.querySelector(selector)
+        
+        // Read and starred flags are only available when the dashboard is served by jornais
+        async function loadReadingState() {
+            if (!location.protocol.startsWith('http')) return
+
+            document.body.classList.add('served')
+
+            const response = await fetch('/api/state')
+            const state = await response.json()
+            const starred = state.starred.map(starredNew => starredNew.link)
+            const post = (url, body) => fetch(url, {
+                method: 'POST',
+                headers: { 'Content-Type': 'application/json' },
+                body: JSON.stringify(body)
+            })
+
+            $(".news").forEach(news => {
+                const link = news.dataset.link
+                const starButton = $1(".starButton", news)
+                let isStarred = starred.includes(link)
+
+                news.classList.toggle('read', state.read.includes(link))
+                starButton.innerText = isStarred ? '★' : '☆'
+
+                $1("a", news).addEventListener('click', () => {
+                    news.classList.add('read')
+                    post('/api/read', { link, read: true })
+                })
+
+                starButton.addEventListener('click', () => {
+                    isStarred = !isStarred
+                    starButton.innerText = isStarred ? '★' : '☆'
+                    post('/api/starred', {
+                        link,
+                        title: $1("h3", news).innerText,
+                        text: $1("b", news).innerText,
+                        newspaper: news.className.split(" ")[1],
+                        starred: isStarred
+                    })
+                })
+            })
+        }
+
+        // Shows the stories that pass every filter of the toolbar together with the titles of the checked newspapers
+        function applyFilters() {
+            const region = $1('#region-filter').value
+            const section = $1('#section-filter').value
+            const kind = $1('#kind-filter').value
+            const paywalled = $1('#paywalled-filter').value
+            const checked = newspaper => $1(`.${newspaper}-checkbox`).checked
+
+            $(".news").forEach(news => {
+                const visible = checked(news.className.split(" ")[1])
+                    && (!region || news.dataset.region === region)
+                    && (!section || news.dataset.section === section)
+                    && (!kind || news.dataset.kind === kind)
+                    && (!paywalled || news.dataset.paywalled === paywalled)
+
+                news.style.display = visible ? 'flex' : 'none'
+            })
+
+            $("h1[data-region]").forEach(title => {
+                const newspaper = title.className.replace('-title', '')
+
+                title.style.display = checked(newspaper) && (!region || title.dataset.region === region) ? 'block' : 'none'
+            })
+        }
+
+        window.onload = function() {
+            loadReadingState()
+
+            const hideButtons = $(".hideButton")
+
+            hideButtons.forEach(button => {
+                button.hiding = false
+                
+                button.addEventListener('click', () => {
+                    const parent = button.parentNode
+
+                    button.hiding = !button.hiding
+                    button.innerText = button.hiding ? '>' : "v"
+
+                    const title = $1("h3", parent)
+                    const text = $1("b", parent)
+
+                    title.style.display = button.hiding ? 'none' : 'block'
+                    text.style.display = button.hiding ? 'none' : 'block'
+                })
+            })
+
+            $('#region-filter, #section-filter, #kind-filter, #paywalled-filter').forEach(select => select.addEventListener('change', applyFilters))
+
+            $(".newspaper-checkbox").forEach(checkbox => {
+                const newspaper = checkbox.dataset.newspaper
+
+                $1(`.${newspaper}Counter`).innerText = $(`.news.${newspaper}`).length.toString()
+
+                checkbox.addEventListener('click', applyFilters)
+            })
+
+        }
+
+
+    </script>
+    "#;
+    
+    format!(r#"
+    <!DOCTYPE html>
+    <html lang="en">
+    <head>
+        <meta charset="UTF-8">
+        <meta name="viewport" content="width=device-width, initial-scale=1.0">
+        <title>Noticias</title>
+        {styles}
+        {script}
+    </head>
+    <body>
+        <div id="toolbar">{toolbar}
+        </div>
+        <div id="counters">
+            {counters}
+        </div>
+        {news_html}
+    </body>
+    </html>
+    "#)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scraped_text_is_escaped() {
+        let journal_new = JournalNew {
+            // The scrapers decode the entities, so an encoded tag in a listing ends up as a tag
+            title: jornais::text::clean("&lt;script&gt;alert(1)&lt;/script&gt; &amp; más"),
+            text: String::from("<img src=x onerror=alert(1)>"),
+            link: Some(String::from("https://www.clarin.com/nota.html?a=1&b=\"2\"")),
+            newspaper: Newspaper::CLARIN,
+            kind: ContentKind::ARTICLE,
+            paywalled: false,
+            section: Section::OTHER,
+            authors: vec![String::from("<b>Juan</b>")],
+            image: None,
+            position: 1,
+            block: None
+        };
+
+        let html = format_news_to_html(String::from("Clarín"), vec![journal_new], Newspaper::CLARIN);
+
+        assert!(html.contains("<h3>&lt;script&gt;alert(1)&lt;/script&gt; &amp; más</h3>"));
+        assert!(html.contains("<b>&lt;img src=x onerror=alert(1)&gt;</b>"));
+        assert!(html.contains(r#"href="https://www.clarin.com/nota.html?a=1&amp;b=&quot;2&quot;""#));
+        assert!(html.contains("Por &lt;b&gt;Juan&lt;/b&gt;"));
+        assert!(!html.contains("<script>"));
+    }
+}
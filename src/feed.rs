@@ -0,0 +1,127 @@
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
+use sha2::{Sha256, Digest};
+
+use crate::model::JournalNew;
+
+fn escape_xml(text: &str) -> String {
+    text
+    .replace('&', "&amp;")
+    .replace('<', "&lt;")
+    .replace('>', "&gt;")
+    .replace('"', "&quot;")
+    .replace('\'', "&apos;")
+}
+
+/// Stable id for the entry, derived from the newspaper and the story key so it survives restarts
+pub fn entry_id(journal_new: &JournalNew) -> String {
+    let digest = Sha256::digest(format!("{}|{}", journal_new.newspaper, journal_new.key()).as_bytes());
+
+    format!("urn:jornais:{}", hex::encode(digest))
+}
+
+fn format_entry(journal_new: &JournalNew, updated: DateTime<Utc>) -> String {
+    let link = match &journal_new.link {
+        Some(link) => format!(r#"<link rel="alternate" href="{}"/>"#, escape_xml(link)),
+        None => String::from("")
+    };
+
+    let thumbnail = match &journal_new.image {
+        Some(image) => format!(
+            r#"<media:thumbnail url="{}"{}{}/>"#,
+            escape_xml(&image.url),
+            image.width.map(|width| format!(r#" width="{width}""#)).unwrap_or_default(),
+            image.height.map(|height| format!(r#" height="{height}""#)).unwrap_or_default()
+        ),
+        None => String::from("")
+    };
+
+    format!(r#"
+    <entry>
+        <id>{}</id>
+        <title>{}</title>
+        {link}
+        <updated>{}</updated>
+        <author><name>{}</name></author>
+        <category term="{}" label="{}"/>
+        <category term="{}" scheme="urn:jornais:section" label="{}"/>
+        {thumbnail}
+        <summary>{}</summary>
+    </entry>"#,
+        entry_id(journal_new),
+        escape_xml(&journal_new.title),
+        updated.to_rfc3339(),
+        journal_new.newspaper,
+        journal_new.newspaper.region(),
+        journal_new.newspaper.region().name(),
+        journal_new.section,
+        journal_new.section.name(),
+        escape_xml(&journal_new.text)
+    )
+}
+
+/// Builds an Atom feed with the given news.
+/// `seen_at` maps each `JournalNew::key` to the first time it was fetched, used as the entry timestamp.
+pub fn format_news_to_atom(feed_id: &str, title: &str, journal_news: &[&JournalNew], seen_at: &HashMap<String, DateTime<Utc>>) -> String {
+    let mut entries: Vec<(&JournalNew, DateTime<Utc>)> = journal_news
+    .iter()
+    .map(|journal_new| (*journal_new, *seen_at.get(&journal_new.key()).unwrap_or(&Utc::now())))
+    .collect();
+
+    entries.sort_by_key(|(_, seen)| std::cmp::Reverse(*seen));
+
+    let updated = entries.first().map(|(_, seen)| *seen).unwrap_or(Utc::now());
+    let entries_xml: String = entries.iter().map(|(journal_new, seen)| format_entry(journal_new, *seen)).collect();
+
+    format!(r#"<?xml version="1.0" encoding="utf-8"?>
+<feed xmlns="http://www.w3.org/2005/Atom" xmlns:media="http://search.yahoo.com/mrss/">
+    <id>urn:jornais:feed:{}</id>
+    <title>{}</title>
+    <updated>{}</updated>{entries_xml}
+</feed>
+"#,
+        escape_xml(feed_id),
+        escape_xml(title),
+        updated.to_rfc3339()
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{testing::sample_new, model::LeadImage};
+
+    #[test]
+    fn escapes_and_keeps_stable_ids() {
+        let mut journal_new = sample_new();
+        journal_new.title = String::from("Ganancias & <retenciones>");
+
+        let seen = chrono::Utc::now() - chrono::Duration::hours(1);
+        let seen_at = std::collections::HashMap::from([(journal_new.key(), seen)]);
+
+        let atom = format_news_to_atom("clarin", "Clarin", &[&journal_new], &seen_at);
+
+        assert!(atom.contains("<title>Ganancias &amp; &lt;retenciones&gt;</title>"));
+        assert!(atom.contains(&format!("<id>{}</id>", entry_id(&journal_new))));
+        assert!(atom.contains(&format!("<updated>{}</updated>", seen.to_rfc3339())));
+        assert_eq!(entry_id(&journal_new), entry_id(&sample_new()));
+    }
+
+    #[test]
+    fn entries_have_their_section_and_thumbnail() {
+        let mut journal_new = sample_new();
+        journal_new.image = Some(LeadImage {
+            url: String::from("https://images.pagina12.com.ar/1.jpg"),
+            alt: None,
+            width: Some(640),
+            height: Some(360),
+            thumbnail: None
+        });
+
+        let atom = format_news_to_atom("all", "Noticias", &[&journal_new], &std::collections::HashMap::new());
+
+        assert!(atom.contains(r#"<category term="politics" scheme="urn:jornais:section" label="Política"/>"#));
+        assert!(atom.contains(r#"<media:thumbnail url="https://images.pagina12.com.ar/1.jpg" width="640" height="360"/>"#));
+    }
+}
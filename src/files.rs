@@ -0,0 +1,84 @@
+use std::{io::Write, path::Path};
+
+/// Writes to a temporary file next to `path` and renames it over `path`,
+/// so readers never see a half written file
+pub fn write_atomically(path: impl AsRef<Path>, contents: impl AsRef<[u8]>) -> std::io::Result<()> {
+    let path = path.as_ref();
+    let mut temporary = path.as_os_str().to_owned();
+    temporary.push(".tmp");
+
+    std::fs::write(&temporary, contents)?;
+    std::fs::rename(&temporary, path)
+}
+
+/// Appends `lines` to `path` in a single write, creating the file when it doesn't exist
+pub fn append_lines(path: impl AsRef<Path>, lines: &[String]) -> std::io::Result<()> {
+    let mut data = String::new();
+
+    for line in lines {
+        data.push_str(line);
+        data.push('\n');
+    }
+
+    std::fs::OpenOptions::new()
+    .create(true)
+    .append(true)
+    .open(path)
+    .and_then(|mut file| file.write_all(data.as_bytes()))
+}
+
+/// Drops the oldest lines of `path` so only the last `keep` remain
+pub fn rotate_lines(path: impl AsRef<Path>, keep: usize) -> std::io::Result<()> {
+    let path = path.as_ref();
+
+    let data = match std::fs::read_to_string(path) {
+        Ok(data) => data,
+        Err(error) if error.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+        Err(error) => return Err(error)
+    };
+
+    let lines: Vec<&str> = data.lines().collect();
+
+    if lines.len() <= keep {
+        return Ok(())
+    }
+
+    let mut kept = lines[lines.len() - keep..].join("\n");
+    kept.push('\n');
+
+    write_atomically(path, kept)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn are_replaced_atomically() {
+        let path = std::env::temp_dir().join(format!("jornais_atomic_test_{}.html", std::process::id()));
+
+        write_atomically(&path, "old").unwrap();
+        write_atomically(&path, "new").unwrap();
+
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "new");
+        assert!(!path.with_extension("html.tmp").exists());
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn appended_lines_are_rotated() {
+        let path = std::env::temp_dir().join(format!("jornais_rotate_test_{}.jsonl", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+
+        append_lines(&path, &[String::from("1"), String::from("2")]).unwrap();
+        append_lines(&path, &[String::from("3")]).unwrap();
+        rotate_lines(&path, 5).unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "1\n2\n3\n");
+
+        rotate_lines(&path, 2).unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "2\n3\n");
+
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,145 @@
+use serde::{Serialize, Deserialize};
+
+use crate::model::{JournalNew, Newspaper, Section};
+
+pub const FRONT_PAGES_FILE: &str = "jornais_front_pages.jsonl";
+
+/// Snapshots kept in the local file, the oldest ones are dropped past this
+const MAX_STORED_FRONT_PAGES: usize = 5000;
+
+/// Group of stories of a front page, like the opening or the sports block
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct FrontPageBlock {
+    /// Name the source gives the block, `None` for the stories outside any block
+    pub name: Option<String>,
+    /// Section of the taxonomy the name of the block maps to
+    pub section: Section,
+    pub stories: Vec<JournalNew>
+}
+
+/// The layout of the home page of a source at a given moment
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct FrontPage {
+    pub newspaper: Newspaper,
+    pub url: String,
+    pub captured_at: String,
+    /// Key of the story the page leads with
+    pub lead: Option<String>,
+    pub blocks: Vec<FrontPageBlock>
+}
+
+impl FrontPage {
+    pub fn lead(&self) -> Option<&JournalNew> {
+        self.stories().find(|journal_new| Some(journal_new.key()) == self.lead)
+    }
+
+    /// Every story of the page, from the top
+    pub fn stories(&self) -> impl Iterator<Item = &JournalNew> {
+        self.blocks.iter().flat_map(|block| &block.stories)
+    }
+}
+
+/// Reads every snapshot stored in `path`, oldest first
+fn read_all(path: &str) -> Vec<FrontPage> {
+    match std::fs::read_to_string(path) {
+        Ok(data) => data.lines().filter_map(|line| serde_json::from_str(line).ok()).collect(),
+        Err(_) => vec![]
+    }
+}
+
+/// Appends the snapshots to the local file used when the database is disabled
+pub fn append(path: &str, front_pages: &[FrontPage]) {
+    let lines: Vec<String> = front_pages
+    .iter()
+    .map(|front_page| serde_json::to_string(front_page).expect("Front pages should be serializable"))
+    .collect();
+
+    if let Err(error) = crate::files::append_lines(path, &lines) {
+        tracing::error!(%error, path, "Couldn't save the front pages")
+    }
+}
+
+/// Drops the oldest snapshots of the local file past `MAX_STORED_FRONT_PAGES`
+pub fn rotate(path: &str) {
+    if let Err(error) = crate::files::rotate_lines(path, MAX_STORED_FRONT_PAGES) {
+        tracing::error!(%error, path, "Couldn't rotate the front pages")
+    }
+}
+
+/// The latest snapshot of each source captured at or before `at`, in the order of `Newspaper::ALL`
+pub fn latest_at(front_pages: Vec<FrontPage>, at: chrono::DateTime<chrono::Utc>) -> Vec<FrontPage> {
+    let mut latest: Vec<FrontPage> = vec![];
+
+    for front_page in front_pages {
+        let captured_at = match chrono::DateTime::parse_from_rfc3339(&front_page.captured_at) {
+            Ok(captured_at) if captured_at <= at => captured_at,
+            _ => continue
+        };
+
+        match latest.iter_mut().find(|latest| latest.newspaper == front_page.newspaper) {
+            Some(latest) if chrono::DateTime::parse_from_rfc3339(&latest.captured_at).is_ok_and(|latest| latest < captured_at) => *latest = front_page,
+            Some(_) => {},
+            None => latest.push(front_page)
+        }
+    }
+
+    latest.sort_by_key(|front_page| Newspaper::ALL.iter().position(|ordered| *ordered == front_page.newspaper));
+
+    latest
+}
+
+/// The snapshots stored in `path` as they were at `at`, optionally only the one of `source`
+pub fn load(path: &str, source: Option<&Newspaper>, at: chrono::DateTime<chrono::Utc>) -> Vec<FrontPage> {
+    let front_pages = read_all(path)
+    .into_iter()
+    .filter(|front_page| source.is_none_or(|source| &front_page.newspaper == source))
+    .collect();
+
+    latest_at(front_pages, at)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::sample_new;
+
+    fn front_page(newspaper: Newspaper, captured_at: &str) -> FrontPage {
+        let journal_new = JournalNew { newspaper: newspaper.clone(), ..sample_new() };
+
+        FrontPage {
+            newspaper,
+            url: String::from("https://www.tn.com.ar"),
+            captured_at: String::from(captured_at),
+            lead: Some(journal_new.key()),
+            blocks: vec![FrontPageBlock { name: Some(String::from("Apertura")), section: Section::OTHER, stories: vec![journal_new] }]
+        }
+    }
+
+    #[test]
+    fn lead_is_one_of_the_stories() {
+        let mut front_page = front_page(Newspaper::TN, "2024-05-01T10:00:00+00:00");
+
+        assert_eq!(front_page.lead(), Some(&JournalNew { newspaper: Newspaper::TN, ..sample_new() }));
+
+        front_page.lead = Some(String::from("https://www.tn.com.ar/otra-nota"));
+        assert_eq!(front_page.lead(), None);
+    }
+
+    #[test]
+    fn are_loaded_as_they_were_at_a_moment() {
+        let path = std::env::temp_dir().join(format!("jornais_front_pages_test_{}.jsonl", std::process::id()));
+        let path = path.to_str().unwrap();
+        let _ = std::fs::remove_file(path);
+
+        let earlier = front_page(Newspaper::TN, "2024-05-01T10:00:00+00:00");
+        let later = FrontPage { blocks: vec![], ..front_page(Newspaper::TN, "2024-05-01T11:00:00+00:00") };
+        let clarin = front_page(Newspaper::CLARIN, "2024-05-01T10:10:00+00:00");
+        append(path, &[earlier.clone(), later.clone(), clarin.clone()]);
+
+        assert_eq!(load(path, None, "2024-05-01T10:30:00Z".parse().unwrap()), [clarin, earlier]);
+        assert_eq!(load(path, Some(&Newspaper::TN), "2024-05-02T00:00:00Z".parse().unwrap()), [later]);
+        assert!(load(path, None, "2024-05-01T09:00:00Z".parse().unwrap()).is_empty());
+
+        let _ = std::fs::remove_file(path);
+    }
+}
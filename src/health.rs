@@ -0,0 +1,252 @@
+use std::{collections::HashMap, fmt::Display};
+
+use serde::{Serialize, Deserialize};
+
+use crate::{runs::FetchStats, model::{JournalNew, Newspaper}};
+
+pub const HEALTH_FILE: &str = "jornais_health.json";
+
+/// Reports kept per source to build the baseline
+const HISTORY_LENGTH: usize = 20;
+
+/// A source returning less than this share of its usual items is considered degraded
+const MIN_ITEMS_RATIO: f64 = 0.5;
+
+/// How much more often a field can be empty compared to the baseline before flagging it
+const EMPTY_FIELD_TOLERANCE: f64 = 0.3;
+
+/// What a single fetch of a source looked like
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct SourceReport {
+    pub checked_at: String,
+    pub items: usize,
+    /// Stories the scraper skipped for missing a title, they aren't part of `items`
+    pub empty_titles: usize,
+    pub empty_texts: usize,
+    /// Stories the scraper skipped for missing a link, they aren't part of `items`
+    pub missing_links: usize,
+    pub error: Option<String>
+}
+
+impl SourceReport {
+    /// The stories without a title or a link never make it into `result`, their counts come from the `stats` of the fetch
+    pub fn new(result: &Result<Vec<JournalNew>, String>, stats: &FetchStats) -> SourceReport {
+        let checked_at = chrono::Utc::now().to_rfc3339();
+
+        match result {
+            Ok(news) => SourceReport {
+                checked_at,
+                items: news.len(),
+                empty_titles: stats.missing_titles,
+                empty_texts: news.iter().filter(|journal_new| journal_new.text.trim().is_empty()).count(),
+                missing_links: stats.missing_links,
+                error: None
+            },
+            Err(error) => SourceReport {
+                checked_at,
+                items: 0,
+                empty_titles: 0,
+                empty_texts: 0,
+                missing_links: 0,
+                error: Some(error.clone())
+            }
+        }
+    }
+}
+
+impl SourceReport {
+    /// Share of the stories found in the listing without a title, a text and a link, in that order.
+    /// The texts are only checked on the parsed stories since the other ones are skipped
+    fn empty_shares(&self) -> [f64; 3] {
+        let found = self.items + self.empty_titles + self.missing_links;
+
+        [ratio(self.empty_titles, found), ratio(self.empty_texts, self.items), ratio(self.missing_links, found)]
+    }
+}
+
+fn ratio(count: usize, items: usize) -> f64 {
+    if items == 0 { 0.0 } else { count as f64 / items as f64 }
+}
+
+fn median(mut values: Vec<f64>) -> f64 {
+    values.sort_by(|a, b| a.total_cmp(b));
+    values[values.len() / 2]
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Eq, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum HealthStatus {
+    HEALTHY,
+    DEGRADED,
+    BROKEN
+}
+
+impl Display for HealthStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::HEALTHY => write!(f, "healthy"),
+            Self::DEGRADED => write!(f, "degraded"),
+            Self::BROKEN => write!(f, "broken")
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct SourceHealth {
+    pub newspaper: Newspaper,
+    pub status: HealthStatus,
+    pub reasons: Vec<String>,
+    pub report: SourceReport
+}
+
+/// Compares a report with the previous successful ones of the same source.
+/// Without a baseline only errors and empty listings are flagged.
+pub fn evaluate(report: &SourceReport, baseline: &[SourceReport]) -> (HealthStatus, Vec<String>) {
+    if let Some(error) = &report.error {
+        return (HealthStatus::BROKEN, vec![format!("fetch failed: {error}")])
+    }
+
+    if report.items == 0 {
+        return (HealthStatus::BROKEN, vec![String::from("no items were parsed")])
+    }
+
+    let baseline: Vec<&SourceReport> = baseline.iter().filter(|previous| previous.error.is_none() && previous.items > 0).collect();
+
+    if baseline.is_empty() {
+        return (HealthStatus::HEALTHY, vec![])
+    }
+
+    let mut reasons = vec![];
+
+    let usual_items = median(baseline.iter().map(|previous| previous.items as f64).collect());
+
+    if (report.items as f64) < usual_items * MIN_ITEMS_RATIO {
+        reasons.push(format!("{} items, usually {}", report.items, usual_items));
+    }
+
+    for (index, field) in ["title", "text", "link"].into_iter().enumerate() {
+        let current = report.empty_shares()[index];
+        let usual = median(baseline.iter().map(|previous| previous.empty_shares()[index]).collect());
+
+        if current > usual + EMPTY_FIELD_TOLERANCE {
+            reasons.push(format!("{field} empty in {:.0}% of items, usually {:.0}%", current * 100.0, usual * 100.0));
+        }
+    }
+
+    if reasons.is_empty() {
+        (HealthStatus::HEALTHY, reasons)
+    } else {
+        (HealthStatus::DEGRADED, reasons)
+    }
+}
+
+/// Latest reports of every source, used as the baseline for the next checks
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(default)]
+pub struct HealthHistory {
+    pub reports: HashMap<Newspaper, Vec<SourceReport>>
+}
+
+impl HealthHistory {
+    pub fn load(path: &str) -> HealthHistory {
+        match std::fs::read_to_string(path) {
+            Ok(data) => serde_json::from_str(&data).unwrap_or_else(|error| {
+                tracing::error!(%error, path, "Couldn't parse the health history, starting from an empty one");
+                HealthHistory::default()
+            }),
+            Err(_) => HealthHistory::default()
+        }
+    }
+
+    pub fn save(&self, path: &str) {
+        let data = serde_json::to_string_pretty(self).expect("The health history should be serializable");
+
+        if let Err(error) = crate::files::write_atomically(path, data) {
+            tracing::error!(%error, path, "Couldn't save the health history")
+        }
+    }
+
+    /// Evaluates the report against the stored ones and adds it to the history
+    pub fn record(&mut self, newspaper: &Newspaper, report: SourceReport) -> SourceHealth {
+        let reports = self.reports.entry(newspaper.clone()).or_default();
+        let (status, reasons) = evaluate(&report, reports);
+
+        reports.push(report.clone());
+
+        if reports.len() > HISTORY_LENGTH {
+            reports.remove(0);
+        }
+
+        SourceHealth { newspaper: newspaper.clone(), status, reasons, report }
+    }
+
+    /// Health of every source according to its last report
+    pub fn latest(&self) -> Vec<SourceHealth> {
+        Newspaper::ALL.iter().filter_map(|newspaper| {
+            let (report, baseline) = self.reports.get(newspaper)?.split_last()?;
+            let (status, reasons) = evaluate(report, baseline);
+
+            Some(SourceHealth { newspaper: newspaper.clone(), status, reasons, report: report.clone() })
+        }).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn report(items: usize, empty_texts: usize) -> SourceReport {
+        SourceReport {
+            checked_at: chrono::Utc::now().to_rfc3339(),
+            items,
+            empty_titles: 0,
+            empty_texts,
+            missing_links: 0,
+            error: None
+        }
+    }
+
+    #[test]
+    fn flags_drops_against_baseline() {
+        let baseline = vec![report(20, 0), report(22, 1), report(19, 0)];
+
+        assert_eq!(evaluate(&report(21, 0), &baseline).0, HealthStatus::HEALTHY);
+        assert_eq!(evaluate(&report(5, 0), &baseline).0, HealthStatus::DEGRADED);
+        assert_eq!(evaluate(&report(20, 18), &baseline).0, HealthStatus::DEGRADED);
+        assert_eq!(evaluate(&report(0, 0), &baseline).0, HealthStatus::BROKEN);
+
+        // Stories skipped for a missing title count against the ones that were found
+        let skipped_titles = SourceReport { empty_titles: 15, ..report(20, 0) };
+        assert_eq!(evaluate(&skipped_titles, &baseline), (HealthStatus::DEGRADED, vec![String::from("title empty in 43% of items, usually 0%")]));
+
+        // Sources that never have a summary aren't flagged for it
+        let without_text = vec![report(20, 20), report(20, 20)];
+        assert_eq!(evaluate(&report(20, 20), &without_text).0, HealthStatus::HEALTHY);
+    }
+
+    #[test]
+    fn reports_parse_errors() {
+        let report = SourceReport::new(&Err(String::from("h2 should exist inside article")), &FetchStats::default());
+        let mut history = HealthHistory::default();
+
+        let source_health = history.record(&Newspaper::CLARIN, report);
+
+        assert_eq!(source_health.status, HealthStatus::BROKEN);
+        assert!(source_health.reasons[0].contains("h2 should exist inside article"));
+        assert_eq!(history.latest().len(), 1);
+
+        let history: HealthHistory = serde_json::from_str(&serde_json::to_string(&history).unwrap()).unwrap();
+        assert_eq!(history.reports[&Newspaper::CLARIN].len(), 1);
+    }
+
+    #[test]
+    fn unreadable_history_starts_empty() {
+        let path = std::env::temp_dir().join(format!("jornais_health_test_{}.json", std::process::id()));
+        let path = path.to_str().unwrap();
+        std::fs::write(path, "{ not json").unwrap();
+
+        assert!(HealthHistory::load(path).reports.is_empty());
+
+        let _ = std::fs::remove_file(path);
+    }
+}
@@ -0,0 +1,533 @@
+use std::{collections::HashMap, path::PathBuf, sync::{Arc, Mutex}, time::Duration};
+
+use reqwest::header;
+use serde::{Serialize, Deserialize};
+use sha2::{Digest, Sha256};
+use tokio::{sync::{OnceCell, Semaphore}, time::Instant};
+
+use crate::robots::Robots;
+
+/// How long a robots.txt is trusted before asking for it again
+const ROBOTS_TTL: Duration = Duration::from_secs(60 * 60 * 24);
+
+/// A robots.txt that couldn't be reached disallows the host until it is asked for again after this
+const UNREACHABLE_ROBOTS_TTL: Duration = Duration::from_secs(60 * 30);
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct HttpConfig {
+    pub user_agent: String,
+    pub connect_timeout_secs: u64,
+    /// Time allowed to get the whole response once connected
+    pub read_timeout_secs: u64,
+    /// Extra attempts after a 5xx answer or a timeout
+    pub max_retries: u32,
+    /// Delay before the first retry, doubled after every failed attempt
+    pub backoff_ms: u64,
+    /// Proxy for every request, like `http://proxy.local:3128` or `socks5://127.0.0.1:1080`.
+    /// Without it the `HTTP_PROXY` and `HTTPS_PROXY` variables are used when set.
+    pub proxy: Option<String>,
+    /// When set the responses are also kept in this directory and reused without a request while younger than `cache_ttl_secs`
+    pub cache_dir: Option<String>,
+    pub cache_ttl_secs: u64,
+    /// When set every listing page fetched is also kept compressed in this directory, see `archive`
+    pub archive_dir: Option<String>,
+    /// Minimum time between two requests to the same host, a longer `Crawl-delay` takes precedence
+    pub host_interval_ms: u64,
+    /// Requests in flight to the same host at once
+    pub max_connections_per_host: usize,
+    /// Fetch the paths disallowed by robots.txt anyway, logging every one of them
+    pub ignore_robots: bool
+}
+
+impl Default for HttpConfig {
+    fn default() -> Self {
+        HttpConfig {
+            user_agent: format!("Mozilla/5.0 (compatible; jornais/{})", env!("CARGO_PKG_VERSION")),
+            connect_timeout_secs: 10,
+            read_timeout_secs: 30,
+            max_retries: 2,
+            backoff_ms: 1000,
+            proxy: None,
+            cache_dir: None,
+            cache_ttl_secs: 600,
+            archive_dir: None,
+            host_interval_ms: 1000,
+            max_connections_per_host: 2,
+            ignore_robots: false
+        }
+    }
+}
+
+/// A page as it was answered by the server
+#[derive(Debug, Clone)]
+pub struct Page {
+    pub status: u16,
+    pub body: String,
+    /// The body is the same one returned the last time, either the server answered 304 or it came from the disk cache
+    pub not_modified: bool,
+    /// Headers and undecoded body of the response, empty when `not_modified` is set
+    pub headers: Vec<(String, String)>,
+    pub raw: Vec<u8>
+}
+
+/// Last successful response of a URL together with its validators
+#[derive(Serialize, Deserialize, Debug, Clone)]
+struct CacheEntry {
+    url: String,
+    status: u16,
+    body: String,
+    etag: Option<String>,
+    last_modified: Option<String>,
+    /// Unix timestamp in seconds
+    fetched_at: i64
+}
+
+/// A page as it came from the server, with its validators
+type Answer = (Page, Option<String>, Option<String>);
+
+#[allow(clippy::upper_case_acronyms)]
+enum AttemptError {
+    TIMEOUT(String),
+    FAILED(String)
+}
+
+/// The robots.txt rules of a host together with when they should be asked for again
+type HostRobots = Arc<OnceCell<(Robots, Instant)>>;
+
+/// What the client knows about a host it requested
+#[derive(Debug)]
+struct Host {
+    /// Shared by the requests to the host so only the first one fetches the robots.txt
+    robots: HostRobots,
+    next_request: Instant,
+    connections: Arc<Semaphore>
+}
+
+/// Client shared by every source so connections are reused
+/// and the limits of each host are kept across every source and stage
+#[derive(Debug, Clone)]
+pub struct HttpClient {
+    client: reqwest::Client,
+    config: HttpConfig,
+    cache: Arc<Mutex<HashMap<String, CacheEntry>>>,
+    hosts: Arc<tokio::sync::Mutex<HashMap<String, Host>>>
+}
+
+impl HttpClient {
+    pub fn new(config: &HttpConfig) -> Result<HttpClient, String> {
+        let mut builder = reqwest::Client::builder()
+        .user_agent(&config.user_agent)
+        .connect_timeout(Duration::from_secs(config.connect_timeout_secs))
+        .gzip(true)
+        .brotli(true);
+
+        if let Some(proxy) = &config.proxy {
+            builder = builder.proxy(reqwest::Proxy::all(proxy).map_err(|error| format!("Invalid proxy {proxy}: {error}"))?);
+        }
+
+        let client = builder.build().map_err(|error| error.to_string())?;
+
+        if let Some(cache_dir) = &config.cache_dir {
+            std::fs::create_dir_all(cache_dir).map_err(|error| format!("Couldn't create the cache directory {cache_dir}: {error}"))?;
+        }
+
+        Ok(HttpClient {
+            client,
+            config: config.clone(),
+            cache: Arc::new(Mutex::new(HashMap::new())),
+            hosts: Arc::new(tokio::sync::Mutex::new(HashMap::new()))
+        })
+    }
+
+    pub fn config(&self) -> &HttpConfig {
+        &self.config
+    }
+
+    /// The underlying client, for requests that handle their own retries like the webhooks
+    pub fn client(&self) -> &reqwest::Client {
+        &self.client
+    }
+
+    fn cache_path(&self, url: &str) -> Option<PathBuf> {
+        let cache_dir = self.config.cache_dir.as_ref()?;
+
+        Some(PathBuf::from(cache_dir).join(format!("{}.json", hex::encode(Sha256::digest(url.as_bytes())))))
+    }
+
+    /// Looks for the last response of `url` in memory and then on disk
+    fn cached(&self, url: &str) -> Option<CacheEntry> {
+        if let Some(entry) = self.cache.lock().expect("The cache lock shouldn't be poisoned").get(url) {
+            return Some(entry.clone())
+        }
+
+        let data = std::fs::read_to_string(self.cache_path(url)?).ok()?;
+        serde_json::from_str(&data).ok()
+    }
+
+    fn store(&self, entry: CacheEntry) {
+        if let Some(path) = self.cache_path(&entry.url) {
+            let data = serde_json::to_string(&entry).expect("Cache entries should be serializable");
+
+            if let Err(error) = crate::files::write_atomically(&path, data) {
+                tracing::warn!(%error, path = %path.display(), "Couldn't write the cache entry");
+            }
+        }
+
+        self.cache.lock().expect("The cache lock shouldn't be poisoned").insert(entry.url.clone(), entry);
+    }
+
+    /// The robots.txt rules of the host of `url`, asked for again once a day. Hosts answering 4xx allow everything,
+    /// hosts that can't be reached or answer 5xx disallow everything for a while, as RFC 9309 asks.
+    async fn robots(&self, url: &reqwest::Url) -> Robots {
+        let host = String::from(url.host_str().unwrap_or_default());
+
+        let robots = {
+            let mut hosts = self.hosts.lock().await;
+            let entry = self.host_entry(&mut hosts, &host);
+
+            if entry.robots.get().is_some_and(|(_, expires_at)| *expires_at <= Instant::now()) {
+                entry.robots = Arc::new(OnceCell::new());
+            }
+
+            entry.robots.clone()
+        };
+
+        let (robots, _) = robots.get_or_init(|| async {
+            let mut robots_url = url.clone();
+            robots_url.set_path("/robots.txt");
+            robots_url.set_query(None);
+            robots_url.set_fragment(None);
+
+            let _permit = self.wait_for_turn(&host, None).await;
+            let request = self.client.get(robots_url.as_str()).timeout(Duration::from_secs(self.config.read_timeout_secs)).send();
+
+            let fetched = match request.await {
+                Ok(response) if response.status().is_success() => response.text().await.map(|text| Robots::parse(&text, &self.config.user_agent)).ok(),
+                Ok(response) if response.status().is_client_error() => Some(Robots::default()),
+                _ => None
+            };
+
+            match fetched {
+                Some(robots) => (robots, Instant::now() + ROBOTS_TTL),
+                None => {
+                    tracing::warn!(host, "Couldn't reach the robots.txt, not requesting the host for now");
+                    (Robots::disallow_all(), Instant::now() + UNREACHABLE_ROBOTS_TTL)
+                }
+            }
+        }).await;
+
+        robots.clone()
+    }
+
+    /// The entry of `host`, added the first time it is requested
+    fn host_entry<'a>(&self, hosts: &'a mut HashMap<String, Host>, host: &str) -> &'a mut Host {
+        hosts.entry(String::from(host)).or_insert_with(|| Host {
+            robots: Arc::new(OnceCell::new()),
+            next_request: Instant::now(),
+            connections: Arc::new(Semaphore::new(self.config.max_connections_per_host.max(1)))
+        })
+    }
+
+    /// Waits until the host accepts another request, returning the permit that holds its connection slot
+    async fn wait_for_turn(&self, host: &str, crawl_delay: Option<f64>) -> tokio::sync::OwnedSemaphorePermit {
+        let connections = {
+            let mut hosts = self.hosts.lock().await;
+            self.host_entry(&mut hosts, host).connections.clone()
+        };
+
+        let permit = connections.acquire_owned().await.expect("The host semaphore is never closed");
+
+        let interval = Duration::from_millis(self.config.host_interval_ms)
+        .max(Duration::from_secs_f64(crawl_delay.unwrap_or(0.0).max(0.0)));
+
+        let turn = {
+            let mut hosts = self.hosts.lock().await;
+            let host = hosts.get_mut(host).expect("The host was added above");
+            let turn = host.next_request.max(Instant::now());
+            host.next_request = turn + interval;
+            turn
+        };
+
+        tokio::time::sleep_until(turn).await;
+
+        permit
+    }
+
+    /// The robots.txt rules of the host of `url`, or an error when they disallow it and `ignore_robots` isn't set
+    async fn allowed_robots(&self, url: &str) -> Result<Robots, String> {
+        let parsed_url = reqwest::Url::parse(url).map_err(|error| format!("Invalid URL {url}: {error}"))?;
+        let robots = self.robots(&parsed_url).await;
+        let path = match parsed_url.query() {
+            Some(query) => format!("{}?{query}", parsed_url.path()),
+            None => String::from(parsed_url.path())
+        };
+
+        if !robots.is_allowed(&path) {
+            if !self.config.ignore_robots {
+                return Err(format!("{url} is disallowed by robots.txt"))
+            }
+
+            tracing::warn!(url, "Fetching a path disallowed by robots.txt because ignore_robots is set");
+        }
+
+        Ok(robots)
+    }
+
+    /// GETs `url` as raw bytes, like an image, keeping to robots.txt and the host limits without retrying or caching
+    pub async fn get_bytes(&self, url: &str) -> Result<Vec<u8>, String> {
+        let robots = self.allowed_robots(url).await?;
+        let host = reqwest::Url::parse(url).ok().and_then(|url| url.host_str().map(String::from)).unwrap_or_default();
+        let _permit = self.wait_for_turn(&host, robots.crawl_delay).await;
+
+        let request = async {
+            let response = self.client.get(url).send().await?.error_for_status()?;
+            response.bytes().await
+        };
+
+        match tokio::time::timeout(Duration::from_secs(self.config.read_timeout_secs), request).await {
+            Ok(Ok(bytes)) => Ok(bytes.to_vec()),
+            Ok(Err(error)) => Err(error.to_string()),
+            Err(_) => Err(format!("no answer after {} seconds", self.config.read_timeout_secs))
+        }
+    }
+
+    async fn try_get(&self, url: &str, cached: Option<&CacheEntry>, crawl_delay: Option<f64>) -> Result<Answer, AttemptError> {
+        let host = reqwest::Url::parse(url).ok().and_then(|url| url.host_str().map(String::from)).unwrap_or_default();
+        let _permit = self.wait_for_turn(&host, crawl_delay).await;
+        let read_timeout = Duration::from_secs(self.config.read_timeout_secs);
+        let mut request = self.client.get(url);
+
+        if let Some(etag) = cached.and_then(|cached| cached.etag.as_ref()) {
+            request = request.header(header::IF_NONE_MATCH, etag);
+        }
+
+        if let Some(last_modified) = cached.and_then(|cached| cached.last_modified.as_ref()) {
+            request = request.header(header::IF_MODIFIED_SINCE, last_modified);
+        }
+
+        let request = async {
+            let response = request.send().await?;
+            let status = response.status().as_u16();
+            let validator = |name| response.headers().get(name).and_then(|value| value.to_str().ok()).map(String::from);
+            let etag = validator(header::ETAG);
+            let last_modified = validator(header::LAST_MODIFIED);
+            let content_type = validator(header::CONTENT_TYPE);
+            let headers = response
+            .headers()
+            .iter()
+            .map(|(name, value)| (name.to_string(), String::from_utf8_lossy(value.as_bytes()).into_owned()))
+            .collect();
+            let raw = response.bytes().await?.to_vec();
+            let body = crate::text::decode_html(&raw, content_type.as_deref());
+
+            Ok::<Answer, reqwest::Error>((Page { status, body, not_modified: false, headers, raw }, etag, last_modified))
+        };
+
+        match tokio::time::timeout(read_timeout, request).await {
+            Ok(Ok(answer)) => Ok(answer),
+            Ok(Err(error)) if error.is_timeout() => Err(AttemptError::TIMEOUT(error.to_string())),
+            Ok(Err(error)) => Err(AttemptError::FAILED(error.to_string())),
+            Err(_) => Err(AttemptError::TIMEOUT(format!("no answer after {} seconds", self.config.read_timeout_secs)))
+        }
+    }
+
+    /// GETs `url`, retrying with exponential backoff on timeouts and 5xx answers.
+    /// Other errors are returned right away, the last 5xx page is returned when the retries run out.
+    ///
+    /// Requests are conditional once an answer had an `ETag` or a `Last-Modified`,
+    /// a 304 gets the previous body back with `not_modified` set.
+    ///
+    /// Paths disallowed by the robots.txt of the host are refused unless `ignore_robots` is set,
+    /// and requests to the same host are spaced and capped by the host limits.
+    pub async fn get(&self, url: &str) -> Result<Page, String> {
+        let cached = self.cached(url);
+        let now = chrono::Utc::now().timestamp();
+
+        if let Some(cached) = &cached {
+            if self.config.cache_dir.is_some() && now - cached.fetched_at < self.config.cache_ttl_secs as i64 {
+                return Ok(Page { status: cached.status, body: cached.body.clone(), not_modified: true, headers: vec![], raw: vec![] })
+            }
+        }
+
+        let robots = self.allowed_robots(url).await?;
+        let mut backoff = Duration::from_millis(self.config.backoff_ms);
+        let mut attempt = 0;
+
+        loop {
+            let result = self.try_get(url, cached.as_ref(), robots.crawl_delay).await;
+
+            let retryable = match &result {
+                Ok((page, _, _)) => page.status >= 500,
+                Err(AttemptError::TIMEOUT(_)) => true,
+                Err(AttemptError::FAILED(_)) => false
+            };
+
+            if retryable && attempt < self.config.max_retries {
+                attempt += 1;
+                tracing::debug!(url, attempt, "Retrying the request");
+                tokio::time::sleep(backoff).await;
+                backoff *= 2;
+                continue
+            }
+
+            return match result {
+                Ok((page, _, _)) if page.status == 304 => match cached {
+                    Some(cached) => {
+                        let body = cached.body.clone();
+                        self.store(CacheEntry { fetched_at: now, ..cached });
+
+                        Ok(Page { status: 304, body, not_modified: true, headers: vec![], raw: vec![] })
+                    },
+                    None => Ok(page)
+                },
+                Ok((page, etag, last_modified)) => {
+                    if (200..300).contains(&page.status) {
+                        self.store(CacheEntry {
+                            url: String::from(url),
+                            status: page.status,
+                            body: page.body.clone(),
+                            etag,
+                            last_modified,
+                            fetched_at: now
+                        });
+                    }
+
+                    Ok(page)
+                },
+                Err(AttemptError::TIMEOUT(error) | AttemptError::FAILED(error)) => Err(error)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{http_client, http_server, http_server_with_robots, http_server_with_robots_response, webhook_receiver};
+
+    #[tokio::test]
+    async fn retries_server_errors() {
+        let (address, received) = webhook_receiver(vec![503, 502, 200]).await;
+        let client = HttpClient::new(&HttpConfig {
+            user_agent: String::from("jornais-test"),
+            backoff_ms: 10,
+            host_interval_ms: 0,
+            ..HttpConfig::default()
+        }).unwrap();
+
+        let page = client.get(&address).await.unwrap();
+        let received = received.await.unwrap();
+
+        assert_eq!(page.status, 200);
+        assert_eq!(received.len(), 3);
+        assert!(received[0].0.to_lowercase().contains("user-agent: jornais-test"));
+        assert!(received[0].0.to_lowercase().contains("accept-encoding: gzip, br"));
+
+        let (address, received) = webhook_receiver(vec![500, 500]).await;
+        let client = HttpClient::new(&HttpConfig { max_retries: 1, backoff_ms: 10, host_interval_ms: 0, ..HttpConfig::default() }).unwrap();
+
+        assert_eq!(client.get(&address).await.unwrap().status, 500);
+        assert_eq!(received.await.unwrap().len(), 2);
+
+        assert!(HttpClient::new(&HttpConfig { proxy: Some(String::from("not a proxy")), ..HttpConfig::default() }).is_err());
+    }
+
+    #[tokio::test]
+    async fn sends_conditional_requests() {
+        let body = "<html>listing</html>";
+        let (address, received) = http_server(vec![
+            format!("HTTP/1.1 200 OK\r\netag: \"v1\"\r\nlast-modified: Wed, 01 May 2024 10:00:00 GMT\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{body}", body.len()),
+            String::from("HTTP/1.1 304 Not Modified\r\nconnection: close\r\n\r\n")
+        ]).await;
+        let client = http_client();
+
+        let first = client.get(&address).await.unwrap();
+        let second = client.get(&address).await.unwrap();
+        let received = received.await.unwrap();
+
+        assert!(!first.not_modified);
+        assert_eq!((second.status, second.body.as_str(), second.not_modified), (304, body, true));
+        assert!(received[1].0.to_lowercase().contains("if-none-match: \"v1\""));
+        assert!(received[1].0.to_lowercase().contains("if-modified-since: wed, 01 may 2024 10:00:00 gmt"));
+    }
+
+    #[tokio::test]
+    async fn reuses_the_disk_cache() {
+        let cache_dir = std::env::temp_dir().join(format!("jornais_cache_test_{}", std::process::id()));
+        let config = HttpConfig { cache_dir: Some(cache_dir.to_str().unwrap().to_string()), host_interval_ms: 0, ..HttpConfig::default() };
+        let (address, received) = http_server(vec![String::from("HTTP/1.1 200 OK\r\ncontent-length: 2\r\nconnection: close\r\n\r\nok")]).await;
+
+        assert_eq!(HttpClient::new(&config).unwrap().get(&address).await.unwrap().body, "ok");
+        assert_eq!(received.await.unwrap().len(), 1);
+
+        // A new client finds the page on disk without asking the server, which is gone by now
+        let cached = HttpClient::new(&config).unwrap().get(&address).await.unwrap();
+        assert_eq!((cached.body.as_str(), cached.not_modified), ("ok", true));
+
+        std::fs::remove_dir_all(cache_dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn honors_robots_and_host_limits() {
+        let ok = String::from("HTTP/1.1 200 OK\r\ncontent-length: 2\r\nconnection: close\r\n\r\nok");
+        let (address, received) = http_server_with_robots(Some("User-agent: *\nDisallow: /hook"), vec![ok.clone()]).await;
+        let config = HttpConfig { host_interval_ms: 0, ..HttpConfig::default() };
+
+        assert!(HttpClient::new(&config).unwrap().get(&address).await.unwrap_err().contains("robots.txt"));
+
+        let ignoring = HttpClient::new(&HttpConfig { ignore_robots: true, ..config }).unwrap();
+        assert_eq!(ignoring.get(&address).await.unwrap().body, "ok");
+        assert_eq!(received.await.unwrap().len(), 1);
+
+        // Requests to the same host are spaced by the host interval
+        let (address, received) = http_server(vec![ok.clone(), ok]).await;
+        let client = HttpClient::new(&HttpConfig { host_interval_ms: 300, ..HttpConfig::default() }).unwrap();
+        let started = std::time::Instant::now();
+
+        client.get(&address).await.unwrap();
+        client.get(&address).await.unwrap();
+
+        assert_eq!(received.await.unwrap().len(), 2);
+        // The robots.txt request took the first turn
+        assert!(started.elapsed() >= std::time::Duration::from_millis(600));
+    }
+
+    #[tokio::test]
+    async fn robots_apply_to_the_query_too() {
+        let ok = String::from("HTTP/1.1 200 OK\r\ncontent-length: 2\r\nconnection: close\r\n\r\nok");
+        let (address, received) = http_server_with_robots(Some("User-agent: *\nDisallow: /hook?page="), vec![ok]).await;
+        let client = http_client();
+
+        assert!(client.get(&format!("{address}?page=2")).await.unwrap_err().contains("robots.txt"));
+        assert_eq!(client.get(&address).await.unwrap().body, "ok");
+        assert_eq!(received.await.unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn unreachable_robots_disallow_the_host() {
+        let ok = String::from("HTTP/1.1 200 OK\r\ncontent-length: 2\r\nconnection: close\r\n\r\nok");
+        let unavailable = String::from("HTTP/1.1 503 Service Unavailable\r\ncontent-length: 0\r\nconnection: close\r\n\r\n");
+        let (address, _, robots_requests) = http_server_with_robots_response(unavailable, vec![ok]).await;
+        let client = http_client();
+
+        assert!(client.get(&address).await.unwrap_err().contains("robots.txt"));
+        // The answer is kept instead of asking again on every request
+        assert!(client.get(&address).await.unwrap_err().contains("robots.txt"));
+        assert_eq!(robots_requests.load(std::sync::atomic::Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn robots_are_fetched_once_per_host() {
+        let ok = String::from("HTTP/1.1 200 OK\r\ncontent-length: 2\r\nconnection: close\r\n\r\nok");
+        let robots = String::from("HTTP/1.1 200 OK\r\ncontent-length: 0\r\nconnection: close\r\n\r\n");
+        let (address, received, robots_requests) = http_server_with_robots_response(robots, vec![ok.clone(), ok.clone(), ok]).await;
+        let client = http_client();
+
+        let (first, second, third) = tokio::join!(client.get(&address), client.get(&address), client.get(&address));
+
+        assert!(first.is_ok() && second.is_ok() && third.is_ok());
+        assert_eq!(received.await.unwrap().len(), 3);
+        assert_eq!(robots_requests.load(std::sync::atomic::Ordering::SeqCst), 1);
+    }
+}
@@ -0,0 +1,125 @@
+use std::{collections::HashSet, io::Cursor, path::Path};
+
+use image::{imageops::FilterType, ImageFormat};
+use serde::{Serialize, Deserialize};
+use sha2::{Digest, Sha256};
+
+use crate::{files, http::HttpClient, model::JournalNew};
+
+/// Where the resized lead images are kept, next to the dashboard
+pub const THUMBNAILS_DIR: &str = "jornais_thumbnails";
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct ImageConfig {
+    /// Download and resize the lead images into `THUMBNAILS_DIR` so the dashboard doesn't hotlink them
+    pub cache: bool,
+    /// Width of the cached thumbnails, the height keeps the proportions
+    pub thumbnail_width: u32
+}
+
+impl Default for ImageConfig {
+    fn default() -> Self {
+        ImageConfig {
+            cache: false,
+            thumbnail_width: 320
+        }
+    }
+}
+
+/// File name of the thumbnail of an image, derived from its URL so it is downloaded only once
+pub fn thumbnail_name(url: &str) -> String {
+    format!("{}.jpg", hex::encode(Sha256::digest(url.as_bytes())))
+}
+
+/// Shrinks an image to `width` keeping its proportions, smaller images are kept as they are, and encodes it as JPEG
+pub fn resize(bytes: &[u8], width: u32) -> Result<Vec<u8>, String> {
+    let image = image::load_from_memory(bytes).map_err(|error| error.to_string())?;
+
+    let image = match image.width() > width {
+        true => image.resize(width, u32::MAX, FilterType::Triangle),
+        false => image
+    };
+
+    let mut thumbnail = Cursor::new(vec![]);
+    image.to_rgb8().write_to(&mut thumbnail, ImageFormat::Jpeg).map_err(|error| error.to_string())?;
+
+    Ok(thumbnail.into_inner())
+}
+
+/// Sets the thumbnail of every story with an image, downloading the ones that aren't in `THUMBNAILS_DIR` yet,
+/// and removes the thumbnails none of the stories uses anymore
+pub async fn cache_thumbnails(client: &HttpClient, config: &ImageConfig, news: &mut [&mut JournalNew]) {
+    let directory = Path::new(THUMBNAILS_DIR);
+
+    if let Err(error) = std::fs::create_dir_all(directory) {
+        tracing::warn!(%error, "Couldn't create the thumbnails directory");
+        return
+    }
+
+    let mut wanted: HashSet<String> = HashSet::new();
+    let mut downloads = tokio::task::JoinSet::new();
+
+    for image in news.iter().filter_map(|journal_new| journal_new.image.as_ref()) {
+        let name = thumbnail_name(&image.url);
+
+        if !wanted.insert(name.clone()) || directory.join(&name).exists() {
+            continue
+        }
+
+        let client = client.clone();
+        let url = image.url.clone();
+        let width = config.thumbnail_width;
+
+        downloads.spawn(async move {
+            let result: Result<(), String> = async {
+                let bytes = client.get_bytes(&url).await?;
+                let thumbnail = tokio::task::spawn_blocking(move || resize(&bytes, width)).await.map_err(|error| error.to_string())??;
+
+                files::write_atomically(Path::new(THUMBNAILS_DIR).join(&name), thumbnail).map_err(|error| error.to_string())
+            }.await;
+
+            if let Err(error) = result {
+                tracing::warn!(url, %error, "Couldn't cache the image");
+            }
+        });
+    }
+
+    while downloads.join_next().await.is_some() {}
+
+    for image in news.iter_mut().filter_map(|journal_new| journal_new.image.as_mut()) {
+        let name = thumbnail_name(&image.url);
+
+        if directory.join(&name).exists() {
+            image.thumbnail = Some(name);
+        }
+    }
+
+    if let Ok(entries) = std::fs::read_dir(directory) {
+        entries
+        .flatten()
+        .filter(|entry| !wanted.contains(entry.file_name().to_string_lossy().as_ref()))
+        .for_each(|entry| {
+            let _ = std::fs::remove_file(entry.path());
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn thumbnails_are_resized() {
+        let mut png = std::io::Cursor::new(vec![]);
+        ::image::RgbImage::new(640, 360).write_to(&mut png, ::image::ImageFormat::Png).unwrap();
+
+        let thumbnail = ::image::load_from_memory(&resize(png.get_ref(), 320).unwrap()).unwrap();
+        assert_eq!((thumbnail.width(), thumbnail.height()), (320, 180));
+
+        let small = ::image::load_from_memory(&resize(png.get_ref(), 1024).unwrap()).unwrap();
+        assert_eq!(small.width(), 640);
+        assert!(resize(b"not an image", 320).is_err());
+        assert_eq!(thumbnail_name("https://images.pagina12.com.ar/1.jpg"), thumbnail_name("https://images.pagina12.com.ar/1.jpg"));
+    }
+}
@@ -1,14 +1,125 @@
+pub mod archive;
+pub mod authors;
+pub mod classify;
+pub mod config;
+pub mod feed;
+pub mod files;
+pub mod frontpage;
+pub mod health;
+pub mod http;
+pub mod images;
+pub mod logging;
+pub mod metrics;
 pub mod model;
 pub mod newspapers;
+pub mod positions;
+pub mod robots;
+pub mod runs;
+pub mod search;
+pub mod state;
+pub mod text;
+pub mod webhook;
+
+#[cfg(test)]
+mod testing;
 
 #[cfg(test)]
 mod tests {
 
-    use crate::newspapers;
+    use crate::{newspapers, testing::http_client, model::{JournalNew, Newspaper, Section}};
+
+    /// Checks the parsed fixture has the expected titles, every link is absolute and there are no repeated links
+    fn assert_fixture_news(news: &[JournalNew], newspaper: Newspaper, titles: &[&str]) {
+        assert_eq!(news.iter().map(|journal_new| journal_new.title.as_str()).collect::<Vec<&str>>(), titles);
+
+        let mut links: Vec<&String> = vec![];
+
+        news.iter().for_each(|journal_new| {
+            let link = journal_new.link.as_ref().expect("Every story should have a link");
+
+            assert_eq!(journal_new.newspaper, newspaper);
+            assert!(link.starts_with("https://"));
+            assert!(!links.contains(&link));
+
+            links.push(link);
+        })
+    }
+
+    #[test]
+    fn newspaper_pagina12_fixture() {
+        let news = newspapers::parse_pagina12(include_str!("../fixtures/pagina12.html")).news;
+
+        assert_fixture_news(&news, Newspaper::PAGINA12, &["El Congreso debate el Presupuesto 2024", "Paritarias docentes en la provincia"]);
+        assert_eq!(news[0].link, Some(String::from("https://www.pagina12.com.ar/712345-el-congreso-debate-el-presupuesto")));
+        assert_eq!(news[0].text, "La oposición anticipó que presentará cambios en el capítulo de subsidios.");
+        assert_eq!(news[1].text, "");
+    }
+
+    #[test]
+    fn newspaper_ambito_fixture() {
+        let news = newspapers::parse_ambito(include_str!("../fixtures/ambito.html")).news;
+
+        assert_fixture_news(&news, Newspaper::AMBITO, &[
+            "Dólar blue hoy: a cuánto cotiza este martes",
+            "Inflación de septiembre: qué esperan las consultoras",
+            "Elecciones 2023: cierre de campaña"
+        ]);
+        assert_eq!(news[1].link, Some(String::from("https://www.ambito.com/economia/inflacion-de-septiembre-n5854300")));
+        assert_eq!(news[2].link, Some(String::from("https://www.ambito.com/politica/elecciones-2023-n5854299")));
+    }
+
+    #[test]
+    fn newspaper_perfil_fixture() {
+        let news = newspapers::parse_perfil(include_str!("../fixtures/perfil.html")).news;
+
+        assert_fixture_news(&news, Newspaper::PERFIL, &["La Corte Suprema falló sobre la coparticipación", "Alerta por tormentas en el AMBA"]);
+        assert_eq!(news[0].text, "El tribunal ordenó restituir fondos a la Ciudad.");
+    }
+
+    #[test]
+    fn newspaper_tn_fixture() {
+        let news = newspapers::parse_tn(include_str!("../fixtures/tn.html")).news;
+
+        assert_fixture_news(&news, Newspaper::TN, &["El Gobierno anunció nuevas medidas económicas", "La Selección ya entrena en Ezeiza"]);
+        assert_eq!(news[0].link, Some(String::from("https://tn.com.ar/politica/2023/10/10/el-gobierno-anuncio-nuevas-medidas/")));
+        assert_eq!(news[1].text, "Scaloni probó variantes para las Eliminatorias.");
+        assert_eq!((news[0].section, news[1].section), (Section::POLITICS, Section::SPORTS));
+    }
+
+    #[test]
+    fn newspaper_lavoz_fixture() {
+        let news = newspapers::parse_lavoz(include_str!("../fixtures/lavoz.html")).news;
+
+        assert_fixture_news(&news, Newspaper::LAVOZ, &["Corte de agua en barrios del sur de Córdoba", "Talleres prepara el clásico"]);
+        assert_eq!(news[0].link, Some(String::from("https://www.lavoz.com.ar/ciudadanos/corte-de-agua-en-barrios-del-sur/")));
+    }
+
+    #[test]
+    fn newspaper_losandes_fixture() {
+        let news = newspapers::parse_losandes(include_str!("../fixtures/losandes.html")).news;
+
+        assert_fixture_news(&news, Newspaper::LOSANDES, &["Vendimia 2024: el cronograma completo", "El precio del vino a granel volvió a subir"]);
+        assert_eq!(news[1].text, "Los productores reclaman por los costos.");
+    }
+
+    #[test]
+    fn newspaper_ellitoral_fixture() {
+        let news = newspapers::parse_ellitoral(include_str!("../fixtures/ellitoral.html")).news;
+
+        assert_fixture_news(&news, Newspaper::ELLITORAL, &["La laguna Setúbal sigue en bajante", "Colón y Unión conocen sus rivales"]);
+    }
+
+    #[test]
+    fn newspaper_rionegro_fixture() {
+        let news = newspapers::parse_rionegro(include_str!("../fixtures/rionegro.html")).news;
+
+        assert_fixture_news(&news, Newspaper::RIONEGRO, &["Vaca Muerta: nuevo récord de producción", "Bariloche cerró una buena temporada de invierno"]);
+        assert_eq!(news[1].link, Some(String::from("https://www.rionegro.com.ar/sociedad/bariloche-temporada-de-invierno-3201200/")));
+    }
 
     #[tokio::test]
     async fn newspaper_clarin() {
-        let news = newspapers::get_clarin().await;
+        let news = newspapers::get_clarin(&http_client()).await;
 
         assert!(!news.is_empty());
 
@@ -25,7 +136,7 @@ mod tests {
 
     #[tokio::test]
     async fn newspaper_infobae() {
-        let news = newspapers::get_infobae().await;
+        let news = newspapers::get_infobae(&http_client()).await;
 
         assert!(!news.is_empty());
 
@@ -42,7 +153,7 @@ mod tests {
 
     #[tokio::test]
     async fn newspaper_lanacion() {
-        let news = newspapers::get_lanacion().await;
+        let news = newspapers::get_lanacion(&http_client()).await;
 
         assert!(!news.is_empty());
 
@@ -59,7 +170,7 @@ mod tests {
 
     #[tokio::test]
     async fn newspaper_lacapital() {
-        let news = newspapers::get_lacapital().await;
+        let news = newspapers::get_lacapital(&http_client()).await;
 
         assert!(!news.is_empty());
 
@@ -76,7 +187,7 @@ mod tests {
 
     #[tokio::test]
     async fn newspaper_rosario3() {
-        let news = newspapers::get_rosario3().await;
+        let news = newspapers::get_rosario3(&http_client()).await;
 
         assert!(!news.is_empty());
 
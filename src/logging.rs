@@ -0,0 +1,69 @@
+use std::{fmt::Display, io::IsTerminal, str::FromStr};
+
+use serde::{Serialize, Deserialize};
+use tracing_subscriber::{filter::LevelFilter, EnvFilter};
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, Eq, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum LogFormat {
+    /// Colored lines meant to be read in a terminal
+    #[default]
+    PRETTY,
+    /// One JSON object per line, for log collectors
+    JSON
+}
+
+impl Display for LogFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::PRETTY => write!(f, "pretty"),
+            Self::JSON => write!(f, "json")
+        }
+    }
+}
+
+impl FromStr for LogFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "pretty" => Ok(Self::PRETTY),
+            "json" => Ok(Self::JSON),
+            _ => Err(format!("Unknown log format {s}, use pretty or json"))
+        }
+    }
+}
+
+/// Sends the logs to stderr with `level` as the default, `RUST_LOG` takes precedence when set
+pub fn init(format: LogFormat, level: LevelFilter) {
+    let filter = EnvFilter::builder()
+    .with_default_directive(level.into())
+    .from_env_lossy();
+
+    let builder = tracing_subscriber::fmt()
+    .with_env_filter(filter)
+    .with_ansi(std::io::stderr().is_terminal())
+    .with_writer(std::io::stderr);
+
+    match format {
+        LogFormat::PRETTY => builder.init(),
+        LogFormat::JSON => builder.json().with_current_span(true).with_span_list(false).init()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn format_is_configurable() {
+        assert_eq!("JSON".parse::<LogFormat>(), Ok(LogFormat::JSON));
+        assert!("xml".parse::<LogFormat>().is_err());
+
+        let config: crate::config::Config = serde_json::from_str(r#"{"log_format": "json"}"#).unwrap();
+        assert_eq!(config.log_format, LogFormat::JSON);
+
+        let config: crate::config::Config = serde_json::from_str("{}").unwrap();
+        assert_eq!(config.log_format, LogFormat::PRETTY);
+    }
+}
@@ -1,8 +1,21 @@
+mod dashboard;
+mod server;
+mod signals;
+mod tui;
+
 use colored::Colorize;
-use jornais::{newspapers, model::{JournalNew, DBInfo, Newspaper}};
-use tokio::{task, time};
-use std::{io::{self, Write}, time::Duration};
+use jornais::{newspapers::{self, SourceFetch}, archive, authors, classify::NewsFilter, files, frontpage::{self, FrontPage}, http::HttpClient, images, metrics::Metrics, positions::{self, PositionRecord}, runs, webhook, feed, health, search, state, config::{self, Config}, logging::{self, LogFormat}, model::{JournalNew, DBInfo, Newspaper, Region, Section}};
+use signals::Signal;
+use tokio::{sync::mpsc, task, time};
+use std::{collections::{HashMap, HashSet, hash_map::Entry}, io::{self, Write}, sync::Arc, time::{Duration, Instant}};
 use sqlx::{mysql::MySqlPoolOptions, Pool, MySql, Row};
+use tracing_subscriber::filter::LevelFilter;
+
+/// How long the nosave mode remembers a story after it leaves every listing
+const SEEN_RETENTION_DAYS: i64 = 7;
+
+/// Cycles of the nosave mode between trims of the local history files, an hour at the default interval
+const ROTATION_CYCLES: usize = 12;
 
 fn menu(title: &str, options: &[&str]) -> String {
     println!("{}", title.bright_green());
@@ -21,7 +34,7 @@ fn menu(title: &str, options: &[&str]) -> String {
 
     match input_result {
         Ok(_) => {},
-        Err(error) => println!("{}", error)
+        Err(error) => tracing::error!(%error, "Couldn't read the answer")
     }
 
     String::from(user_input.trim())
@@ -37,7 +50,224 @@ fn _print_news(title: &str, news: Vec<JournalNew>) {
     });
 }
 
-async fn save_news_to_database(pool: &Pool<MySql>, news: JournalNew, newspaper_name: String) {
+fn print_starred(user: &str, regions: &[Region]) {
+    let mut starred = state::ReadingState::load(state::STATE_FILE).user(user).starred;
+    starred.retain(|starred_new| starred_new.newspaper.in_regions(regions));
+
+    println!("{}", format!("[ {} starred news for {user} ]", starred.len()).green());
+    let separator = "----------------".bright_white();
+
+    starred.iter().for_each(|starred_new| {
+        println!(
+            "\n{}\n [{}] {} \n {} \n{}",
+            separator,
+            starred_new.newspaper.info().name.bright_white(),
+            starred_new.title.blue().bold(),
+            starred_new.link,
+            separator
+        )
+    });
+}
+
+/// Records how each source did in the health history, warning about the ones that look broken
+fn record_health(results: &[newspapers::SourceFetch]) -> Vec<health::SourceHealth> {
+    let mut history = health::HealthHistory::load(health::HEALTH_FILE);
+
+    let source_health: Vec<health::SourceHealth> = results
+    .iter()
+    .map(|(newspaper, result, stats)| history.record(newspaper, health::SourceReport::new(result, stats)))
+    .collect();
+
+    history.save(health::HEALTH_FILE);
+
+    for source in &source_health {
+        let reasons = source.reasons.join(", ");
+
+        match source.status {
+            health::HealthStatus::HEALTHY => {},
+            health::HealthStatus::DEGRADED => tracing::warn!(source = %source.newspaper, reasons, "The source looks degraded"),
+            health::HealthStatus::BROKEN => tracing::error!(source = %source.newspaper, reasons, "The source looks broken")
+        }
+    }
+
+    source_health
+}
+
+/// Fetches every source once and prints its health, exits with 1 when any of them is broken
+async fn check_sources(http: &HttpClient, regions: &[Region]) {
+    let results = newspapers::get_all_checked(http, regions, &mut newspapers::LastParsed::default()).await;
+    let source_health = record_health(&results);
+
+    println!("{}", "[ Source health ]".green());
+
+    for source in &source_health {
+        let status = match source.status {
+            health::HealthStatus::HEALTHY => source.status.to_string().green(),
+            health::HealthStatus::DEGRADED => source.status.to_string().yellow(),
+            health::HealthStatus::BROKEN => source.status.to_string().red()
+        };
+
+        println!("{:<10} {:<22} {:>3} items  {}", status, source.newspaper.info().name, source.report.items, source.reasons.join(", "));
+    }
+
+    if source_health.iter().any(|source| source.status == health::HealthStatus::BROKEN) {
+        std::process::exit(1)
+    }
+}
+
+/// Sends the new story to the configured webhooks in the background so retries don't hold up the loop,
+/// the delivery is added to `deliveries` so it can be awaited on shutdown
+fn notify_webhooks(deliveries: &mut Vec<task::JoinHandle<()>>, client: &reqwest::Client, config: &Config, journal_new: &JournalNew) {
+    if config.webhooks.urls.is_empty() || !config.webhooks.wants(journal_new) {
+        return
+    }
+
+    let client = client.clone();
+    let webhooks = config.webhooks.clone();
+    let payload = webhook::WebhookPayload::new(journal_new, chrono::Utc::now());
+
+    deliveries.retain(|delivery| !delivery.is_finished());
+    deliveries.push(task::spawn(async move {
+        webhook::notify(&client, &webhooks, &payload).await
+    }));
+}
+
+/// Waits for the webhook deliveries that are still retrying, for at most 30 seconds
+async fn wait_for_deliveries(deliveries: Vec<task::JoinHandle<()>>) {
+    let pending: Vec<task::JoinHandle<()>> = deliveries.into_iter().filter(|delivery| !delivery.is_finished()).collect();
+
+    if pending.is_empty() {
+        return
+    }
+
+    tracing::info!(deliveries = pending.len(), "Waiting for the pending webhook deliveries");
+
+    let waiting = async {
+        for delivery in pending {
+            let _ = delivery.await;
+        }
+    };
+
+    if time::timeout(Duration::from_secs(30), waiting).await.is_err() {
+        tracing::warn!("Gave up waiting for the webhook deliveries");
+    }
+}
+
+/// Prints the error of a bad command line in red and exits with 1
+fn exit_with_error(error: &str) -> ! {
+    println!("{}", format!("[ {error} ]").red());
+    std::process::exit(1)
+}
+
+/// Applies the command line flags that override the config file, like `--ignore-robots` or `--section politics,economy`
+fn apply_flags(args: &[String], config: &mut Config) -> Result<(), String> {
+    if args.iter().any(|arg| arg == "--ignore-robots") {
+        config.http.ignore_robots = true;
+    }
+
+    if args.iter().any(|arg| arg == "--archive") {
+        config.http.archive_dir.get_or_insert(String::from(archive::ARCHIVE_DIR));
+    }
+
+    if let Some(kinds) = flag_value(args, "--kind") {
+        config.filter.kinds = NewsFilter::parse_kinds(kinds)?;
+    }
+
+    if let Some(sections) = flag_value(args, "--section") {
+        config.filter.sections = NewsFilter::parse_sections(sections)?;
+    }
+
+    if let Some(paywalled) = flag_value(args, "--paywalled") {
+        config.filter.paywalled = Some(NewsFilter::parse_paywalled(paywalled)?);
+    }
+
+    if let Some(front_pages) = flag_value(args, "--front-pages") {
+        config.front_pages = match front_pages.as_str() {
+            "all" => Newspaper::ALL.to_vec(),
+            front_pages => front_pages
+            .split(',')
+            .map(|newspaper| newspaper.parse())
+            .collect::<Result<Vec<Newspaper>, String>>()?
+        };
+    }
+
+    if let Some(authors) = flag_value(args, "--author") {
+        config.filter.authors = NewsFilter::parse_authors(authors);
+    }
+
+    Ok(())
+}
+
+/// Snapshots the home pages of the sources of the config that are in `regions`
+async fn capture_front_pages(http: &HttpClient, config: &Config, regions: &[Region]) -> Vec<FrontPage> {
+    let newspapers: Vec<Newspaper> = config.front_pages.iter().filter(|newspaper| newspaper.in_regions(regions)).cloned().collect();
+
+    if newspapers.is_empty() {
+        return vec![]
+    }
+
+    newspapers::get_front_pages(http, &newspapers).await
+}
+
+/// Drops the stories the filter leaves out, like the sponsored ones, so they are neither stored nor notified
+fn filter_results(results: &mut [SourceFetch], filter: &NewsFilter) {
+    for (_, result, _) in results.iter_mut() {
+        if let Ok(news) = result {
+            news.retain(|journal_new| filter.matches(journal_new));
+        }
+    }
+}
+
+/// Waits for the next tick of `interval`, reloading the config on SIGHUP meanwhile.
+/// Returns `false` when the loop should stop.
+async fn wait_for_next_cycle(
+    interval: &mut time::Interval,
+    signals: &mut mpsc::UnboundedReceiver<Signal>,
+    args: &[String],
+    config: &mut Config,
+    regions: &mut Vec<Region>,
+    http: &mut HttpClient
+) -> bool {
+    loop {
+        // Signals go first so a pending shutdown wins over a tick that got ready while a cycle overran
+        tokio::select! {
+            biased;
+
+            signal = signals.recv() => match signal {
+                Some(Signal::RELOAD) => match Config::try_load(config::CONFIG_FILE) {
+                    Ok(mut reloaded) => {
+                        // The flags were checked on start, only the reloaded config can make them fail
+                        let reloaded_regions = match apply_flags(args, &mut reloaded).and_then(|_| parse_regions(args, &reloaded)) {
+                            Ok(reloaded_regions) => reloaded_regions,
+                            Err(error) => {
+                                tracing::error!(%error, "Couldn't apply the flags to the reloaded config, keeping the current one");
+                                continue
+                            }
+                        };
+
+                        if reloaded.http != config.http {
+                            match HttpClient::new(&reloaded.http) {
+                                Ok(reloaded_http) => *http = reloaded_http,
+                                Err(error) => tracing::error!(%error, "Couldn't rebuild the HTTP client, keeping the current one")
+                            }
+                        }
+
+                        *regions = reloaded_regions;
+                        *config = reloaded;
+                        tracing::info!("Reloaded the config");
+                    },
+                    Err(error) => tracing::error!(%error, "Couldn't reload the config, keeping the current one")
+                },
+                Some(Signal::SHUTDOWN) | None => return false
+            },
+            _ = interval.tick() => return true
+        }
+    }
+}
+
+/// Returns `true` when the title wasn't stored yet and got inserted
+#[tracing::instrument(name = "save", skip_all, fields(source = %news.newspaper))]
+async fn save_news_to_database(pool: &Pool<MySql>, news: &JournalNew) -> bool {
     let title = news.title.as_str();
     let text = news.text.as_str();
     let posted_on = "1999-12-12";
@@ -49,7 +279,11 @@ async fn save_news_to_database(pool: &Pool<MySql>, news: JournalNew, newspaper_n
 
             if count == 0 {
                 match sqlx::query(
-                    "INSERT INTO news (title, text, posted_on, newspaper_name) VALUES (
+                    "INSERT INTO news (title, text, posted_on, newspaper_name, kind, paywalled, section, authors) VALUES (
+                        ?,
+                        ?,
+                        ?,
+                        ?,
                         ?,
                         ?,
                         ?,
@@ -59,136 +293,892 @@ async fn save_news_to_database(pool: &Pool<MySql>, news: JournalNew, newspaper_n
                 .bind(title)
                 .bind(text)
                 .bind(posted_on)
-                .bind(newspaper_name)
+                .bind(news.newspaper.to_string())
+                .bind(news.kind.to_string())
+                .bind(news.paywalled)
+                .bind(news.section.to_string())
+                .bind(serde_json::to_string(&news.authors).expect("Authors should be serializable"))
                 .execute(pool).await {
-                    Ok(_) => println!("{}", "[ Saved a new title ]".green()),
-                    Err(e) => println!("{}", e)
+                    Ok(_) => {
+                        tracing::info!(title, "Saved a new title");
+                        return true
+                    },
+                    Err(error) => tracing::error!(%error, "Couldn't save the title")
                 };
             }
         },
-        Err(error) => println!("{}", error)
+        Err(error) => tracing::error!(%error, "Couldn't look for the title")
+    };
+
+    false
+}
+
+/// Authors of a stored story, kept in the `authors` column as a JSON list
+fn stored_authors(row: &sqlx::mysql::MySqlRow) -> Vec<String> {
+    row.try_get::<Option<String>, _>("authors")
+    .ok()
+    .flatten()
+    .and_then(|authors| serde_json::from_str(&authors).ok())
+    .unwrap_or_default()
+}
+
+/// Stores a story found again in an archived page, inserting it with the time of that fetch when it is missing
+/// or updating what the scraper derives from the markup when it is there. Returns `true` when it got inserted.
+async fn backfill_news(pool: &Pool<MySql>, news: &JournalNew, fetched_at: chrono::DateTime<chrono::Utc>) -> Result<bool, sqlx::Error> {
+    let authors = serde_json::to_string(&news.authors).expect("Authors should be serializable");
+    let row = sqlx::query("SELECT COUNT(*) as count FROM news WHERE title = ?").bind(&news.title).fetch_one(pool).await?;
+
+    if row.try_get::<i64, _>("count")? > 0 {
+        sqlx::query("UPDATE news SET kind = ?, paywalled = ?, section = ?, authors = ? WHERE title = ?")
+        .bind(news.kind.to_string())
+        .bind(news.paywalled)
+        .bind(news.section.to_string())
+        .bind(authors)
+        .bind(&news.title)
+        .execute(pool).await?;
+
+        return Ok(false)
+    }
+
+    sqlx::query("INSERT INTO news (title, text, saved_on, posted_on, newspaper_name, kind, paywalled, section, authors) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)")
+    .bind(&news.title)
+    .bind(&news.text)
+    .bind(fetched_at)
+    .bind(fetched_at.date_naive())
+    .bind(news.newspaper.to_string())
+    .bind(news.kind.to_string())
+    .bind(news.paywalled)
+    .bind(news.section.to_string())
+    .bind(authors)
+    .execute(pool).await?;
+
+    Ok(true)
+}
+
+/// Runs the current scrapers over the archived listings, backfilling and fixing the stored news unless `pool` is `None`
+async fn reparse_archive(directory: &str, entries: &[archive::ArchiveEntry], filter: &NewsFilter, pool: Option<&Pool<MySql>>) {
+    let (mut stories, mut inserted, mut updated, mut failed) = (0, 0, 0, 0);
+
+    for entry in entries {
+        let fetched_at = chrono::DateTime::parse_from_rfc3339(&entry.fetched_at)
+        .map(|fetched_at| fetched_at.with_timezone(&chrono::Utc))
+        .unwrap_or(chrono::Utc::now());
+
+        let mut news = match archive::reparse(directory, entry) {
+            Ok(news) => news,
+            Err(error) => {
+                failed += 1;
+                println!("{}", format!("{:<20} {:<12} {}", fetched_at.with_timezone(&chrono::Local).format("%d-%m-%Y %H:%M:%S"), entry.newspaper.to_string(), error).red());
+                continue
+            }
+        };
+
+        news.retain(|journal_new| filter.matches(journal_new));
+        stories += news.len();
+        println!("{:<20} {:<12} {:>4} items", fetched_at.with_timezone(&chrono::Local).format("%d-%m-%Y %H:%M:%S"), entry.newspaper.to_string(), news.len());
+
+        if let Some(pool) = pool {
+            for journal_new in &news {
+                match backfill_news(pool, journal_new, fetched_at).await {
+                    Ok(true) => inserted += 1,
+                    Ok(false) => updated += 1,
+                    Err(error) => tracing::error!(%error, title = journal_new.title, "Couldn't backfill the story")
+                }
+            }
+        }
+    }
+
+    println!("{}", format!("[ Reparsed {} pages, {failed} failed, {stories} stories ]", entries.len()).green());
+
+    if pool.is_some() {
+        println!("{}", format!("[ {inserted} stories added, {updated} updated ]").green());
+    }
+}
+
+/// Adds a FULLTEXT index over `columns` to a table created before the index existed
+async fn add_fulltext_index(pool: &Pool<MySql>, table: &str, index: &str, columns: &str) {
+    let exists = sqlx::query("
+        SELECT COUNT(*) as count FROM information_schema.STATISTICS
+        WHERE TABLE_SCHEMA = DATABASE() AND TABLE_NAME = ? AND INDEX_NAME = ?
+    ")
+    .bind(table)
+    .bind(index)
+    .fetch_one(pool).await
+    .map(|row| row.try_get::<i64, _>("count").unwrap_or_default() > 0);
+
+    match exists {
+        Ok(true) => {},
+        Ok(false) => match sqlx::query(&format!("ALTER TABLE {table} ADD FULLTEXT INDEX {index} ({columns})")).execute(pool).await {
+            Ok(_) => tracing::info!(table, index, "Added a FULLTEXT index"),
+            Err(error) => panic!("{error}")
+        },
+        Err(error) => tracing::error!(%error, table, index, "Couldn't check the indexes of the table")
+    }
+}
+
+/// Adds a column to a table created by an older version, doing nothing when it is already there
+async fn add_column(pool: &Pool<MySql>, table: &str, column: &str, definition: &str) {
+    let exists = sqlx::query("
+        SELECT COUNT(*) as count FROM information_schema.COLUMNS
+        WHERE TABLE_SCHEMA = DATABASE() AND TABLE_NAME = ? AND COLUMN_NAME = ?
+    ")
+    .bind(table)
+    .bind(column)
+    .fetch_one(pool).await
+    .map(|row| row.try_get::<i64, _>("count").unwrap_or_default() > 0);
+
+    match exists {
+        Ok(true) => {},
+        Ok(false) => match sqlx::query(&format!("ALTER TABLE {table} ADD COLUMN {column} {definition}")).execute(pool).await {
+            Ok(_) => tracing::info!(table, column, "Added a column"),
+            Err(error) => panic!("{error}")
+        },
+        Err(error) => tracing::error!(%error, table, column, "Couldn't check the columns of the table")
+    }
+}
+
+#[tracing::instrument(name = "save_runs", skip_all, fields(runs = runs.len()))]
+async fn save_runs_to_database(pool: &Pool<MySql>, runs: &[runs::RunRecord]) {
+    for run in runs {
+        let started_at = chrono::DateTime::parse_from_rfc3339(&run.started_at)
+        .map(|started_at| started_at.with_timezone(&chrono::Utc))
+        .unwrap_or(chrono::Utc::now());
+
+        if let Err(error) = sqlx::query(
+            "INSERT INTO runs (newspaper_name, started_at, duration_ms, http_status, bytes, items, new_items, error) VALUES (?, ?, ?, ?, ?, ?, ?, ?)"
+        )
+        .bind(run.newspaper.to_string())
+        .bind(started_at)
+        .bind(run.duration_ms)
+        .bind(run.http_status)
+        .bind(run.bytes as u64)
+        .bind(run.items as u64)
+        .bind(run.new_items as u64)
+        .bind(&run.error)
+        .execute(pool).await {
+            tracing::error!(source = %run.newspaper, %error, "Couldn't save the run")
+        }
+    }
+}
+
+#[tracing::instrument(name = "save_positions", skip_all, fields(positions = positions.len()))]
+async fn save_positions_to_database(pool: &Pool<MySql>, positions: &[PositionRecord]) {
+    for position in positions {
+        let fetched_at = chrono::DateTime::parse_from_rfc3339(&position.fetched_at)
+        .map(|fetched_at| fetched_at.with_timezone(&chrono::Utc))
+        .unwrap_or(chrono::Utc::now());
+
+        if let Err(error) = sqlx::query(
+            "INSERT INTO positions (newspaper_name, story, title, section, fetched_at, position, block) VALUES (?, ?, ?, ?, ?, ?, ?)"
+        )
+        .bind(position.newspaper.to_string())
+        .bind(&position.story)
+        .bind(&position.title)
+        .bind(position.section.to_string())
+        .bind(fetched_at)
+        .bind(position.position as u64)
+        .bind(&position.block)
+        .execute(pool).await {
+            tracing::error!(source = %position.newspaper, %error, "Couldn't save the position")
+        }
+    }
+}
+
+async fn load_positions_from_database(pool: &Pool<MySql>, source: Option<&Newspaper>, since: Option<chrono::DateTime<chrono::Utc>>) -> Vec<PositionRecord> {
+    let rows = match sqlx::query("
+        SELECT newspaper_name, story, title, section, fetched_at, position, block FROM positions
+        WHERE (? IS NULL OR newspaper_name = ?) AND (? IS NULL OR fetched_at >= ?)
+        ORDER BY fetched_at, id
+    ")
+    .bind(source.map(|source| source.to_string()))
+    .bind(source.map(|source| source.to_string()))
+    .bind(since)
+    .bind(since)
+    .fetch_all(pool).await {
+        Ok(rows) => rows,
+        Err(error) => {
+            tracing::error!(%error, "Couldn't load the positions");
+            return vec![]
+        }
+    };
+
+    rows.iter().filter_map(|row| {
+        let fetched_at: chrono::DateTime<chrono::Utc> = row.try_get("fetched_at").ok()?;
+
+        Some(PositionRecord {
+            newspaper: row.try_get::<String, _>("newspaper_name").ok()?.parse().ok()?,
+            story: row.try_get("story").ok()?,
+            title: row.try_get("title").ok()?,
+            section: row.try_get::<String, _>("section").ok().and_then(|section| section.parse().ok()).unwrap_or_default(),
+            fetched_at: fetched_at.to_rfc3339(),
+            position: row.try_get::<u16, _>("position").ok()? as usize,
+            block: row.try_get("block").ok()?
+        })
+    }).collect()
+}
+
+#[tracing::instrument(name = "save_front_pages", skip_all, fields(front_pages = front_pages.len()))]
+async fn save_front_pages_to_database(pool: &Pool<MySql>, front_pages: &[FrontPage]) {
+    for front_page in front_pages {
+        let captured_at = chrono::DateTime::parse_from_rfc3339(&front_page.captured_at)
+        .map(|captured_at| captured_at.with_timezone(&chrono::Utc))
+        .unwrap_or(chrono::Utc::now());
+
+        if let Err(error) = sqlx::query("INSERT INTO front_pages (newspaper_name, captured_at, lead_title, snapshot) VALUES (?, ?, ?, ?)")
+        .bind(front_page.newspaper.to_string())
+        .bind(captured_at)
+        .bind(front_page.lead().map(|lead| lead.title.clone()))
+        .bind(serde_json::to_string(front_page).expect("Front pages should be serializable"))
+        .execute(pool).await {
+            tracing::error!(source = %front_page.newspaper, %error, "Couldn't save the front page")
+        }
+    }
+}
+
+/// The latest snapshot of each source captured at or before `at`
+async fn load_front_pages_from_database(pool: &Pool<MySql>, source: Option<&Newspaper>, at: chrono::DateTime<chrono::Utc>) -> Vec<FrontPage> {
+    let rows = match sqlx::query("
+        SELECT front_pages.snapshot FROM front_pages
+        JOIN (
+            SELECT newspaper_name, MAX(captured_at) AS captured_at FROM front_pages
+            WHERE captured_at <= ? AND (? IS NULL OR newspaper_name = ?)
+            GROUP BY newspaper_name
+        ) latest ON front_pages.newspaper_name = latest.newspaper_name AND front_pages.captured_at = latest.captured_at
+    ")
+    .bind(at)
+    .bind(source.map(|source| source.to_string()))
+    .bind(source.map(|source| source.to_string()))
+    .fetch_all(pool).await {
+        Ok(rows) => rows,
+        Err(error) => {
+            tracing::error!(%error, "Couldn't load the front pages");
+            return vec![]
+        }
     };
 
+    let front_pages = rows
+    .iter()
+    .filter_map(|row| serde_json::from_str(&row.try_get::<String, _>("snapshot").ok()?).ok())
+    .collect();
 
+    frontpage::latest_at(front_pages, at)
 }
 
-fn format_news_to_html(title: String, journal_news: Vec<JournalNew>, newspaper: Newspaper) -> String {
+fn print_front_pages(front_pages: &[FrontPage], at: chrono::DateTime<chrono::Utc>, stories: usize) {
+    println!("{}", format!("[ Front pages at {} ]", at.with_timezone(&chrono::Local).format("%d-%m-%Y %H:%M")).green());
+    let separator = "----------------".bright_white();
+
+    for front_page in front_pages {
+        let captured_at = chrono::DateTime::parse_from_rfc3339(&front_page.captured_at)
+        .map(|captured_at| captured_at.with_timezone(&chrono::Local).format("%d-%m-%Y %H:%M").to_string())
+        .unwrap_or(front_page.captured_at.clone());
+
+        println!("\n{}\n {} {}", separator, front_page.newspaper.info().name.bright_white().bold(), format!("({captured_at})").bright_black());
+
+        if let Some(lead) = front_page.lead() {
+            println!(" {} {}", "Lead:".green(), lead.title.blue().bold());
+        }
+
+        for block in &front_page.blocks {
+            let name = block.name.clone().unwrap_or(String::from("-"));
 
-    let mut news_html = String::from(format!(r#"
-        <h1 class="{}-title">{title}</h1>
-    "#, newspaper));
+            match block.section {
+                Section::OTHER => println!("  [{name}]"),
+                section => println!("  [{name}] {}", section.name().bright_black())
+            }
+
+            for journal_new in block.stories.iter().take(stories) {
+                println!("  {:>3}. {}", journal_new.position, journal_new.title)
+            }
+        }
+    }
+}
 
-    for news in journal_news {
-        let formatted = format!(r#"
-        <div class="news {}">
-            <button class="hideButton">v</button>
-            <a target="_blank" href="{}">
-                <h3>{}</h3>
-            </a>
-            <b>{}</b>
-        </div>
-        "#,
-        news.newspaper,
-        news.link.unwrap_or(String::from("")),
-        news.title,
-        news.text,
+/// Parses `--at` values like `2024-05-01 10:00` in local time or RFC 3339 timestamps
+fn parse_moment(at: &str) -> Option<chrono::DateTime<chrono::Utc>> {
+    if let Ok(at) = chrono::DateTime::parse_from_rfc3339(at) {
+        return Some(at.with_timezone(&chrono::Utc))
+    }
+
+    chrono::NaiveDateTime::parse_from_str(at, "%Y-%m-%d %H:%M")
+    .ok()
+    .and_then(|at| at.and_local_timezone(chrono::Local).earliest())
+    .map(|at| at.with_timezone(&chrono::Utc))
+}
+
+/// The positions of the stories of every source that could be fetched
+fn snapshot_positions(results: &[SourceFetch]) -> Vec<PositionRecord> {
+    results
+    .iter()
+    .filter_map(|(_, result, stats)| Some(PositionRecord::snapshot(result.as_ref().ok()?, &stats.started_at)))
+    .flatten()
+    .collect()
+}
+
+fn print_positions(stays: &[positions::TopStay], shares: &[(Newspaper, Vec<(Section, f64)>)], top: usize) {
+    println!("{}", format!("[ {} stories reached the top {top} ]", stays.len()).green());
+    println!("{:<12} {:>9} {:>4} {:>5}  title", "source", "time", "best", "seen");
+
+    for stay in stays {
+        let duration = stay.duration();
+
+        println!(
+            "{:<12} {:>9} {:>4} {:>5}  {}",
+            stay.newspaper.to_string(),
+            format!("{}h {:02}m", duration.num_hours(), duration.num_minutes() % 60),
+            stay.best_position,
+            stay.snapshots,
+            stay.title.blue()
+        )
+    }
+
+    println!("\n{}", format!("[ Sections of the top {top} ]").green());
+
+    for (newspaper, sections) in shares {
+        let sections: Vec<String> = sections.iter().map(|(section, share)| format!("{} {:.0}%", section.name(), share * 100.0)).collect();
+
+        println!("{:<22} {}", newspaper.info().name.bright_white(), sections.join(", "))
+    }
+}
+
+async fn load_runs_from_database(pool: &Pool<MySql>, source: Option<&Newspaper>, limit: usize) -> Vec<runs::RunRecord> {
+    let rows = match sqlx::query("
+        SELECT newspaper_name, started_at, duration_ms, http_status, bytes, items, new_items, error FROM runs
+        WHERE (? IS NULL OR newspaper_name = ?)
+        ORDER BY started_at DESC, id DESC
+        LIMIT ?
+    ")
+    .bind(source.map(|source| source.to_string()))
+    .bind(source.map(|source| source.to_string()))
+    .bind(limit as u64)
+    .fetch_all(pool).await {
+        Ok(rows) => rows,
+        Err(error) => {
+            tracing::error!(%error, "Couldn't load the runs");
+            return vec![]
+        }
+    };
+
+    rows.iter().filter_map(|row| {
+        let started_at: chrono::DateTime<chrono::Utc> = row.try_get("started_at").ok()?;
+
+        Some(runs::RunRecord {
+            newspaper: row.try_get::<String, _>("newspaper_name").ok()?.parse().ok()?,
+            started_at: started_at.to_rfc3339(),
+            duration_ms: row.try_get::<u32, _>("duration_ms").ok()? as u64,
+            http_status: row.try_get("http_status").ok()?,
+            bytes: row.try_get::<u32, _>("bytes").ok()? as usize,
+            items: row.try_get::<u32, _>("items").ok()? as usize,
+            new_items: row.try_get::<u32, _>("new_items").ok()? as usize,
+            error: row.try_get("error").ok()?
+        })
+    }).collect()
+}
+
+fn print_runs(runs: &[runs::RunRecord]) {
+    println!("{}", format!("[ {} runs ]", runs.len()).green());
+    println!("{:<20} {:<12} {:>8} {:>6} {:>9} {:>6} {:>4}  error", "started", "source", "duration", "status", "bytes", "items", "new");
+
+    for run in runs {
+        let started_at = chrono::DateTime::parse_from_rfc3339(&run.started_at)
+        .map(|started_at| started_at.with_timezone(&chrono::Local).format("%d-%m-%Y %H:%M:%S").to_string())
+        .unwrap_or(run.started_at.clone());
+
+        let status = run.http_status.map(|status| status.to_string()).unwrap_or(String::from("-"));
+        let line = format!(
+            "{:<20} {:<12} {:>6}ms {:>6} {:>9} {:>6} {:>4}  {}",
+            started_at,
+            run.newspaper.to_string(),
+            run.duration_ms,
+            status,
+            run.bytes,
+            run.items,
+            run.new_items,
+            run.error.clone().unwrap_or_default()
         );
 
-        news_html += formatted.as_str();
+        if run.error.is_some() {
+            println!("{}", line.red())
+        } else {
+            println!("{}", line)
+        }
+    }
+}
+
+async fn load_authors_from_database(pool: &Pool<MySql>, source: Option<&Newspaper>, since: Option<chrono::DateTime<chrono::Utc>>) -> Vec<(Newspaper, Vec<String>)> {
+    let rows = match sqlx::query("
+        SELECT newspaper_name, authors FROM news
+        WHERE authors IS NOT NULL AND (? IS NULL OR newspaper_name = ?) AND (? IS NULL OR saved_on >= ?)
+    ")
+    .bind(source.map(|source| source.to_string()))
+    .bind(source.map(|source| source.to_string()))
+    .bind(since)
+    .bind(since)
+    .fetch_all(pool).await {
+        Ok(rows) => rows,
+        Err(error) => {
+            tracing::error!(%error, "Couldn't load the authors");
+            return vec![]
+        }
+    };
+
+    rows.iter().filter_map(|row| {
+        let newspaper = row.try_get::<String, _>("newspaper_name").ok()?.parse().ok()?;
+
+        Some((newspaper, stored_authors(row)))
+    }).collect()
+}
+
+/// Authors of the stories of the last cycle of the HTML mode
+fn load_authors_from_news_file(source: Option<&Newspaper>) -> Vec<(Newspaper, Vec<String>)> {
+    let news: Vec<JournalNew> = std::fs::read_to_string(server::NEWS_FILE)
+    .ok()
+    .and_then(|data| serde_json::from_str(&data).ok())
+    .unwrap_or_default();
+
+    news
+    .into_iter()
+    .filter(|journal_new| source.is_none_or(|source| journal_new.newspaper == *source))
+    .map(|journal_new| (journal_new.newspaper, journal_new.authors))
+    .collect()
+}
+
+fn print_authors(per_source: &[(Newspaper, authors::AuthorCounts)]) {
+    println!("{}", "[ Most prolific authors ]".green());
+
+    for (newspaper, authors) in per_source {
+        println!("\n{}", newspaper.info().name.bright_white().bold());
+
+        for (author, stories) in authors {
+            println!("{:>5}  {}", stories.to_string().blue(), author)
+        }
+    }
+}
+
+/// Returns the value following a flag like `--since 7d`
+fn flag_value<'a>(args: &'a [String], flag: &str) -> Option<&'a String> {
+    args.iter().position(|arg| arg == flag).and_then(|index| args.get(index + 1))
+}
+
+async fn search_news(pool: &Pool<MySql>, query: &str, since: Option<&String>, source: Option<&String>, regions: &[Region], filter: &NewsFilter) {
+    let since = match since.map(|since| search::parse_since(since)) {
+        Some(Ok(since)) => Some(chrono::Utc::now() - since),
+        Some(Err(error)) => {
+            println!("{}", format!("[ --since {error} ]").red());
+            return
+        },
+        None => None
+    };
+
+    let source = match source.map(|source| source.parse::<Newspaper>()) {
+        Some(Ok(newspaper)) => Some(newspaper),
+        Some(Err(error)) => {
+            println!("{}", format!("[ {error} ]").red());
+            return
+        },
+        None => None
+    };
+
+    let fulltext_query = search::fulltext_query(query);
+
+    let newspapers: Vec<String> = Newspaper::ALL
+    .iter()
+    .filter(|newspaper| newspaper.in_regions(regions) && source.as_ref().is_none_or(|source| source == *newspaper))
+    .map(|newspaper| newspaper.to_string())
+    .collect();
+
+    if fulltext_query.is_empty() || newspapers.is_empty() {
+        println!("{}", format!("[ 0 results for \"{query}\" ]").green());
+        return
     }
 
-    news_html
+    let kinds: Vec<String> = filter.allowed_kinds().iter().map(|kind| kind.to_string()).collect();
+    let sections: Vec<String> = filter.sections.iter().map(|section| section.to_string()).collect();
+    let placeholders = |count: usize| vec!["?"; count].join(", ");
+
+    // Every filter but the authors, which are matched in any spelling below, runs in the query so the limit
+    // only applies to the stories that pass them
+    let sql = format!("
+        SELECT title, text, newspaper_name, saved_on, kind, paywalled, section, authors FROM news
+        WHERE MATCH(title, text) AGAINST(? IN BOOLEAN MODE)
+        AND (? IS NULL OR saved_on >= ?) AND newspaper_name IN ({}) AND kind IN ({}) AND (? IS NULL OR paywalled = ?) {}
+        ORDER BY MATCH(title, text) AGAINST(? IN BOOLEAN MODE) DESC
+        LIMIT ?
+    ", placeholders(newspapers.len()), placeholders(kinds.len()), match sections.is_empty() {
+        true => String::new(),
+        false => format!("AND section IN ({})", placeholders(sections.len()))
+    });
+
+    let mut statement = sqlx::query(&sql)
+    .bind(&fulltext_query)
+    .bind(since)
+    .bind(since);
+
+    for value in newspapers.iter().chain(&kinds) {
+        statement = statement.bind(value);
+    }
+
+    statement = statement.bind(filter.paywalled).bind(filter.paywalled);
+
+    for section in &sections {
+        statement = statement.bind(section);
+    }
+
+    // Without an author filter the query already returns only the stories to rank
+    let limit = match filter.authors.is_empty() {
+        true => search::MAX_CANDIDATES as u64,
+        false => u64::MAX
+    };
+
+    let rows = match statement.bind(&fulltext_query).bind(limit).fetch_all(pool).await {
+        Ok(rows) => rows,
+        Err(error) => {
+            tracing::error!(%error, "Couldn't search the news");
+            return
+        }
+    };
+
+    // The FULLTEXT index narrows the news down to the candidates, they are ranked with BM25 below
+    let stored: Vec<(String, String, String, chrono::DateTime<chrono::Utc>)> = rows
+    .iter()
+    .filter(|row| filter.authors.is_empty() || authors::signed_by(&stored_authors(row), &filter.authors))
+    .take(search::MAX_CANDIDATES as usize)
+    .map(|row| (
+        row.try_get("title").unwrap_or_default(),
+        row.try_get::<Option<String>, _>("text").unwrap_or_default().unwrap_or_default(),
+        row.try_get("newspaper_name").unwrap_or_default(),
+        row.try_get("saved_on").unwrap_or_default()
+    )).collect();
+
+    let documents: Vec<(&str, &str)> = stored.iter().map(|(title, text, _, _)| (title.as_str(), text.as_str())).collect();
+    let ranked = search::rank(query, &documents);
+
+    println!("{}", format!("[ {} results for \"{query}\" ]", ranked.len()).green());
+    let separator = "----------------".bright_white();
+
+    for (index, score) in ranked {
+        let (title, text, newspaper_name, saved_on) = &stored[index];
+
+        println!(
+            "\n{}\n [{}] {} {} \n {} \n{}",
+            separator,
+            newspaper_name.bright_white(),
+            title.blue().bold(),
+            format!("({score:.2}, {})", saved_on.format("%d-%m-%Y %H:%M")).bright_black(),
+            text,
+            separator
+        )
+    }
+}
+
+/// Regions given with `--region santafe,cordoba`, falling back to the ones in the config file
+fn parse_regions(args: &[String], config: &Config) -> Result<Vec<Region>, String> {
+    match flag_value(args, "--region") {
+        Some(regions) => regions
+        .split(',')
+        .map(|region| region.parse())
+        .collect(),
+        None => Ok(config.regions.clone())
+    }
+}
+
+fn load_db_info() -> DBInfo {
+    let mut load_file = false;
+    let db_info_fp = std::path::Path::new("db_conn.json");
+
+    if db_info_fp.exists() {
+        let user_load = menu("There are database connection settings saved, do you want to load them?", &["Yes", "No"]);
+
+        if user_load.as_str() == "0" {
+            tracing::info!("Loading the database connection settings from db_conn.json");
+            load_file = true;
+        }
+    }
+
+
+    let mut db_info = DBInfo {
+        user: String::from(""),
+        password: String::from(""),
+        name: String::from(""),
+        port: String::from("")
+    };
+
+    if !load_file {
+        db_info.user = menu("Enter your MySQL username", &[]);
+        db_info.password = menu("Enter your MySQL password", &[]);
+        db_info.name = menu("Enter the name of the database you want to use", &[]);
+        db_info.port = menu("Enter the port where the database server is running", &[]);
+
+        let save_to_file = menu("Do you want to save these connection settings to a file? (This will remove previously saved settings)", &["Yes", "No"]);
+
+        if save_to_file.as_str() == "0" {
+            let mut file = match std::fs::File::create("db_conn.json") {
+                Ok(f) => f,
+                Err(_) => panic!("Error trying to retrieve db_conn.json file")
+            };
+        
+            serde_json::to_writer_pretty(&mut file, &db_info).expect("Error trying to save database info");
+        }
+    } else {
+        let data = std::fs::read_to_string(db_info_fp).expect("Error retrieving file contents of db_conn.json");
+
+        db_info = serde_json::from_str(&data).expect("Error parsing db_conn.json");
+    }
 
+    db_info
+}
+
+async fn connect_to_database(db_info: &DBInfo) -> Pool<MySql> {
+    tracing::info!(user = db_info.user, database = db_info.name, port = db_info.port, "Connecting to the database on localhost");
+
+    match MySqlPoolOptions::new()
+    .max_connections(2)
+    .connect(format!(
+        "mysql://{}:{}@localhost:{}/{}",
+        db_info.user,
+        db_info.password,
+        db_info.port,
+        db_info.name
+    ).as_str()).await {
+        Ok(pool) => pool,
+        Err(error) => panic!("
+        Error connecting to database: 
+        Make sure you created a database with the name 'newspapers'.
+        {}
+        ", error)
+    }
 }
 
 #[tokio::main]
 async fn main() {
 
     let args: Vec<String> = std::env::args().collect();
+    let mut config = Config::load(config::CONFIG_FILE);
 
-    if args[1] != "nosave" {
+    if let Err(error) = apply_flags(&args, &mut config) {
+        exit_with_error(&error)
+    }
 
-        let mut load_file = false;
-        let db_info_fp = std::path::Path::new("db_conn.json");
-    
-        if db_info_fp.exists() {
-            let user_load = menu("There are database connection settings saved, do you want to load them?", &["Yes", "No"]);
-    
-            if user_load.as_str() == "0" {
-                println!("{}", "[ Loading information to connect to database from file ]".green());
-                load_file = true;
-            }
-        }
-    
-    
-        let mut db_info = DBInfo {
-            user: String::from(""),
-            password: String::from(""),
-            name: String::from(""),
-            port: String::from("")
+    let mut http = HttpClient::new(&config.http).unwrap_or_else(|error| panic!("{error}"));
+    let mut regions = parse_regions(&args, &config).unwrap_or_else(|error| exit_with_error(&error));
+
+    let log_format = match flag_value(&args, "--log-format").map(|log_format| log_format.parse::<LogFormat>()) {
+        Some(Ok(log_format)) => log_format,
+        Some(Err(error)) => exit_with_error(&error),
+        None => config.log_format
+    };
+    let quiet = args.iter().any(|arg| arg == "--quiet");
+
+    // Logs would be drawn on top of the interface
+    let log_level = if args[1] == "tui" {
+        LevelFilter::OFF
+    } else if quiet {
+        LevelFilter::WARN
+    } else {
+        LevelFilter::INFO
+    };
+
+    logging::init(log_format, log_level);
+
+    if args[1] == "tui" {
+        tui::run(http, config.user, regions, config.filter).await;
+        return
+    }
+
+    if args[1] == "starred" {
+        let user = args.get(2).filter(|user| !user.starts_with("--")).unwrap_or(&config.user);
+        print_starred(user, &regions);
+        return
+    }
+
+    if args[1] == "check" {
+        check_sources(&http, &regions).await;
+        return
+    }
+
+    if args[1] == "runs" {
+        let source = match flag_value(&args, "--source").map(|source| source.parse::<Newspaper>()) {
+            Some(Ok(newspaper)) => Some(newspaper),
+            Some(Err(error)) => {
+                println!("{}", format!("[ {error} ]").red());
+                return
+            },
+            None => None
         };
-    
-        if !load_file {
-            db_info.user = menu("Enter your MySQL username", &[]);
-            db_info.password = menu("Enter your MySQL password", &[]);
-            db_info.name = menu("Enter the name of the database you want to use", &[]);
-            db_info.port = menu("Enter the port where the database server is running", &[]);
-    
-            let save_to_file = menu("Do you want to save these connection settings to a file? (This will remove previously saved settings)", &["Yes", "No"]);
-    
-            if save_to_file.as_str() == "0" {
-                let mut file = match std::fs::File::create("db_conn.json") {
-                    Ok(f) => f,
-                    Err(_) => panic!("Error trying to retrieve db_conn.json file")
-                };
-            
-                serde_json::to_writer_pretty(&mut file, &db_info).expect("Error trying to save database info");
-            }
+        let limit = flag_value(&args, "--limit").and_then(|limit| limit.parse().ok()).unwrap_or(50);
+
+        let stored_runs = if args.iter().any(|arg| arg == "nosave") {
+            runs::load(runs::RUNS_FILE, source.as_ref(), limit)
         } else {
-            let data = std::fs::read_to_string(db_info_fp).expect("Error retrieving file contents of db_conn.json");
-    
-            db_info = serde_json::from_str(&data).expect("Error parsing db_conn.json");
+            let pool = connect_to_database(&load_db_info()).await;
+            load_runs_from_database(&pool, source.as_ref(), limit).await
+        };
+
+        print_runs(&stored_runs);
+        return
+    }
+
+    if args[1] == "authors" {
+        let source = match flag_value(&args, "--source").map(|source| source.parse::<Newspaper>()) {
+            Some(Ok(newspaper)) => Some(newspaper),
+            Some(Err(error)) => {
+                println!("{}", format!("[ {error} ]").red());
+                return
+            },
+            None => None
+        };
+        let since = match flag_value(&args, "--since").map(|since| search::parse_since(since)) {
+            Some(Ok(since)) => Some(chrono::Utc::now() - since),
+            Some(Err(error)) => {
+                println!("{}", format!("[ --since {error} ]").red());
+                return
+            },
+            None => None
+        };
+        let limit = flag_value(&args, "--limit").and_then(|limit| limit.parse().ok()).unwrap_or(10);
+
+        let mut stories = if args.iter().any(|arg| arg == "nosave") {
+            load_authors_from_news_file(source.as_ref())
+        } else {
+            let pool = connect_to_database(&load_db_info()).await;
+            load_authors_from_database(&pool, source.as_ref(), since).await
+        };
+
+        stories.retain(|(newspaper, _)| newspaper.in_regions(&regions));
+        print_authors(&authors::most_prolific(&stories, limit));
+        return
+    }
+
+    if args[1] == "reparse" {
+        let source = match flag_value(&args, "--source").map(|source| source.parse::<Newspaper>()) {
+            Some(Ok(newspaper)) => Some(newspaper),
+            Some(Err(error)) => {
+                println!("{}", format!("[ {error} ]").red());
+                return
+            },
+            None => None
+        };
+        let since = match flag_value(&args, "--since").map(|since| search::parse_since(since)) {
+            Some(Ok(since)) => Some(chrono::Utc::now() - since),
+            Some(Err(error)) => {
+                println!("{}", format!("[ --since {error} ]").red());
+                return
+            },
+            None => None
+        };
+        let directory = config.http.archive_dir.clone().unwrap_or(String::from(archive::ARCHIVE_DIR));
+        let mut entries = archive::entries(&directory, source.as_ref(), since);
+        entries.retain(|entry| entry.newspaper.in_regions(&regions));
+
+        if args.iter().any(|arg| arg == "nosave") {
+            reparse_archive(&directory, &entries, &config.filter, None).await;
+        } else {
+            let pool = connect_to_database(&load_db_info()).await;
+            reparse_archive(&directory, &entries, &config.filter, Some(&pool)).await;
         }
-        
-    
-        println!("
-            {}
-            [Username] {}
-            [Password] {}
-            [Database] {}
-            [Port]     {}    
-        ",
-            "[ Using the following variables to connect to the database on localhost ]".blue(), 
-            db_info.user.green(), 
-            db_info.password.green(), 
-            db_info.name.green(), 
-            db_info.port.green()
-        );
-    
-        let pool = match MySqlPoolOptions::new()
-        .max_connections(2)
-        .connect(format!(
-            "mysql://{}:{}@localhost:{}/{}",
-            db_info.user,
-            db_info.password,
-            db_info.port,
-            db_info.name
-        ).as_str()).await {
-            Ok(pool) => pool,
-            Err(error) => panic!("
-            Error connecting to database: 
-            Make sure you created a database with the name 'newspapers'.
-            {}
-            ", error)
+
+        return
+    }
+
+    if args[1] == "frontpages" {
+        let source = match flag_value(&args, "--source").map(|source| source.parse::<Newspaper>()) {
+            Some(Ok(newspaper)) => Some(newspaper),
+            Some(Err(error)) => {
+                println!("{}", format!("[ {error} ]").red());
+                return
+            },
+            None => None
+        };
+        let at = match flag_value(&args, "--at").map(|at| parse_moment(at)) {
+            Some(Some(at)) => at,
+            Some(None) => {
+                println!("{}", "[ --at should look like 2024-05-01 10:00 ]".red());
+                return
+            },
+            None => chrono::Utc::now()
+        };
+        let stories = flag_value(&args, "--stories").and_then(|stories| stories.parse().ok()).unwrap_or(3);
+
+        let mut front_pages = if args.iter().any(|arg| arg == "nosave") {
+            frontpage::load(frontpage::FRONT_PAGES_FILE, source.as_ref(), at)
+        } else {
+            let pool = connect_to_database(&load_db_info()).await;
+            load_front_pages_from_database(&pool, source.as_ref(), at).await
+        };
+
+        front_pages.retain(|front_page| front_page.newspaper.in_regions(&regions));
+        print_front_pages(&front_pages, at, stories);
+        return
+    }
+
+    if args[1] == "positions" {
+        let source = match flag_value(&args, "--source").map(|source| source.parse::<Newspaper>()) {
+            Some(Ok(newspaper)) => Some(newspaper),
+            Some(Err(error)) => {
+                println!("{}", format!("[ {error} ]").red());
+                return
+            },
+            None => None
+        };
+        let since = match flag_value(&args, "--since").map(|since| search::parse_since(since)) {
+            Some(Ok(since)) => Some(chrono::Utc::now() - since),
+            Some(Err(error)) => {
+                println!("{}", format!("[ --since {error} ]").red());
+                return
+            },
+            None => Some(chrono::Utc::now() - chrono::Duration::days(1))
+        };
+        let top = flag_value(&args, "--top").and_then(|top| top.parse().ok()).unwrap_or(3);
+        let limit = flag_value(&args, "--limit").and_then(|limit| limit.parse().ok()).unwrap_or(20);
+
+        let mut stored_positions = if args.iter().any(|arg| arg == "nosave") {
+            positions::load(positions::POSITIONS_FILE, source.as_ref(), since)
+        } else {
+            let pool = connect_to_database(&load_db_info()).await;
+            load_positions_from_database(&pool, source.as_ref(), since).await
+        };
+
+        stored_positions.retain(|position| position.newspaper.in_regions(&regions));
+
+        let mut stays = positions::top_stays(&stored_positions, top);
+        stays.truncate(limit);
+
+        print_positions(&stays, &positions::section_shares(&stored_positions, top), top);
+        return
+    }
+
+    if args[1] == "search" {
+        let query = match args.get(2) {
+            Some(query) => query,
+            None => {
+                println!("{}", "[ Usage: jornais search <query> [--since 7d] [--source clarin] [--region santafe] [--kind live,video] [--section politics] [--paywalled no] [--author \"Juan Pérez\"] ]".red());
+                return
+            }
         };
+
+        let pool = connect_to_database(&load_db_info()).await;
+        search_news(&pool, query, flag_value(&args, "--since"), flag_value(&args, "--source"), &regions, &config.filter).await;
+        return
+    }
+
+    let metrics = Arc::new(Metrics::default());
+
+    if let Some(address) = config.metrics_address.clone() {
+        task::spawn(server::serve_metrics(address, metrics.clone()));
+    }
+
+    if args[1] != "nosave" {
+
+        let db_info = load_db_info();
+        let pool = connect_to_database(&db_info).await;
     
-        println!("{}", "[ Creating table to save news ]".bright_green());
+        tracing::info!("Creating table to save news");
     
         match sqlx::query(
             "CALL sys.table_exists(?, 'newspapers', @exists); SELECT @exists"
         ).bind(db_info.name).execute(&pool).await {
-            Ok(exists) => println!("EXISTS {:?}", exists),
-            Err(error) => println!("ERROR {}", error)
+            Ok(exists) => tracing::debug!(?exists, "Checked the newspapers table"),
+            Err(error) => tracing::error!(%error, "Couldn't check the newspapers table")
         }
     
         match sqlx::query(
@@ -198,328 +1188,226 @@ async fn main() {
                 text TEXT,
                 saved_on TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
                 posted_on DATE,
-                newspaper_name CHAR(40) NOT NULL
+                newspaper_name CHAR(40) NOT NULL,
+                kind CHAR(20) NOT NULL DEFAULT 'article',
+                paywalled BOOLEAN NOT NULL DEFAULT FALSE,
+                section CHAR(20) NOT NULL DEFAULT 'other',
+                authors TEXT
             )"
         ).execute(&pool).await {
-            Ok(_) => println!("{}", "[ Created table to save news ]".bright_green()),
+            Ok(_) => tracing::info!("Created table to save news"),
             Err(error) => panic!("{error}")
         };
 
-        let _ = task::spawn(async move {
-            // Will execute every 20 minutes
-            let mut interval = time::interval(Duration::from_secs(60 * 20));
-            println!("{}", "[ Starting to look for new titles ]".bright_blue());
-    
-            loop {
-                interval.tick().await;
-    
-                let (
-                    clarin_news,
-                    infobae_news,
-                    lanacion_news,
-                    lacapital_news,
-                    rosario3_news
-                ) = tokio::join!(
-                    newspapers::get_clarin(),
-                    newspapers::get_infobae(),
-                    newspapers::get_lanacion(),
-                    newspapers::get_lacapital(),
-                    newspapers::get_rosario3()
-                );
-    
-                for journal_new in clarin_news {
-                    save_news_to_database(&pool, journal_new, String::from("clarin")).await
-                }
-    
-                for journal_new in infobae_news {
-                    save_news_to_database(&pool, journal_new, String::from("infobae")).await
-                }
-    
-                for journal_new in lanacion_news {
-                    save_news_to_database(&pool, journal_new, String::from("la nacion")).await
-                }
-    
-                for journal_new in lacapital_news {
-                    save_news_to_database(&pool, journal_new, String::from("la capital")).await
-                }
-    
-                for journal_new in rosario3_news {
-                    save_news_to_database(&pool, journal_new, String::from("rosario3")).await
-                }
-            }
-        }).await;
-    } else {
-        print!("{esc}[2J{esc}[1;1H", esc = 27 as char); // Clear the screen
-        let _ = task::spawn(async move {
-            let mut interval = time::interval(Duration::from_secs(60 * 5));
-            loop {
-                interval.tick().await;
-    
-                let (
-                    clarin_news,
-                    rosario3_news,
-                    infobae_news,
-                    lacapital_news,
-                    lanacion_news
-                ) = tokio::join!(
-                    newspapers::get_clarin(),
-                    newspapers::get_rosario3(),
-                    newspapers::get_infobae(),
-                    newspapers::get_lacapital(),
-                    newspapers::get_lanacion()
-                );
-
-                
-                
-                let clarin_html = format_news_to_html(String::from("Clarin"), clarin_news, Newspaper::CLARIN);
-                let rosario3_html = format_news_to_html(String::from("Rosario3"), rosario3_news, Newspaper::ROSARIO3);
-                let infobae_html = format_news_to_html(String::from("Infobae"), infobae_news, Newspaper::INFOBAE);
-                let lacapital_html = format_news_to_html(String::from("La Capital"), lacapital_news, Newspaper::LACAPITAL);
-                let lanacion_html = format_news_to_html(String::from("La Nacion"), lanacion_news, Newspaper::LANACION);
-
-                let styles = r#"
-                <style>
-                    body {
-                        display: flex;
-                        flex-direction: column;
-                        align-items: center;
-                        background-color: rgba(44, 74, 83, 0.74);
-                        color: rgb(0, 0, 0);
-                        text-align: center;
-                    }
+        match sqlx::query(
+            "CREATE TABLE IF NOT EXISTS runs (
+                id INT NOT NULL AUTO_INCREMENT PRIMARY KEY,
+                newspaper_name CHAR(40) NOT NULL,
+                started_at TIMESTAMP NOT NULL,
+                duration_ms INT UNSIGNED NOT NULL,
+                http_status SMALLINT UNSIGNED,
+                bytes INT UNSIGNED NOT NULL,
+                items INT UNSIGNED NOT NULL,
+                new_items INT UNSIGNED NOT NULL,
+                error TEXT
+            )"
+        ).execute(&pool).await {
+            Ok(_) => tracing::info!("Created table to save runs"),
+            Err(error) => panic!("{error}")
+        };
 
-                    .news {
-                        display: flex;
-                        flex-direction: column;
-                        align-items: center;
-                        width: 75%;
-                        background-color: rgba(196, 188, 177, 0.288);
-                        padding: 10px;
-                        border-radius: 5px;
-                        border: 2px solid rgba(247, 203, 121, 0.363);
-                        margin-bottom: 10px;
-                    }
+        match sqlx::query(
+            "CREATE TABLE IF NOT EXISTS positions (
+                id INT NOT NULL AUTO_INCREMENT PRIMARY KEY,
+                newspaper_name CHAR(40) NOT NULL,
+                story VARCHAR(500) NOT NULL,
+                title CHAR(200) NOT NULL,
+                section CHAR(20) NOT NULL DEFAULT 'other',
+                fetched_at TIMESTAMP NOT NULL,
+                position SMALLINT UNSIGNED NOT NULL,
+                block CHAR(60),
+                INDEX (newspaper_name, fetched_at)
+            )"
+        ).execute(&pool).await {
+            Ok(_) => tracing::info!("Created table to save positions"),
+            Err(error) => panic!("{error}")
+        };
 
-                    .news > b {
-                        font-size: 12px;
-                        text-align: justify;
-                    }
-                    
-                    h3 {
-                        font-size: 24px;
-                    }
+        match sqlx::query(
+            "CREATE TABLE IF NOT EXISTS front_pages (
+                id INT NOT NULL AUTO_INCREMENT PRIMARY KEY,
+                newspaper_name CHAR(40) NOT NULL,
+                captured_at TIMESTAMP NOT NULL,
+                lead_title CHAR(200),
+                snapshot MEDIUMTEXT NOT NULL,
+                INDEX (newspaper_name, captured_at)
+            )"
+        ).execute(&pool).await {
+            Ok(_) => tracing::info!("Created table to save front pages"),
+            Err(error) => panic!("{error}")
+        };
 
-                    h1 {
-                        color: rgb(0, 0, 0);
-                    }
+        add_column(&pool, "news", "kind", "CHAR(20) NOT NULL DEFAULT 'article'").await;
+        add_column(&pool, "news", "paywalled", "BOOLEAN NOT NULL DEFAULT FALSE").await;
+        add_column(&pool, "news", "section", "CHAR(20) NOT NULL DEFAULT 'other'").await;
+        add_column(&pool, "news", "authors", "TEXT").await;
+        add_fulltext_index(&pool, "news", "news_search", "title, text").await;
+
+        // Older versions stored some names with spaces, the stored name is now always the source id
+        for newspaper in [Newspaper::LANACION, Newspaper::LACAPITAL] {
+            let legacy_name = newspaper.info().name.to_lowercase();
+
+            if let Err(error) = sqlx::query("UPDATE news SET newspaper_name = ? WHERE newspaper_name = ?")
+            .bind(newspaper.to_string())
+            .bind(legacy_name)
+            .execute(&pool).await {
+                tracing::error!(source = %newspaper, %error, "Couldn't migrate the legacy newspaper name")
+            }
+        }
 
-                    .hideButton {
-                        align-self: flex-start;
-                        font-size: 16px;
-                        background-color: rgba(255, 191, 52, 0.699);
-                        border: none;
-                        border-radius: 5px;
-                        padding: 5px 10px
-                    }
+        if let Some(address) = config.server_address.clone() {
+            task::spawn(server::serve(address, config.user.clone(), Some(pool.clone())));
+        }
 
-                    a {
-                        text-decoration: none;
-                        color: rgb(2, 51, 92);
-                    }
+        let mut signals = signals::listen();
 
-                    a:hover {
-                        text-decoration: underline;
-                    }
+        let _ = task::spawn(async move {
+            // Will execute every 20 minutes
+            let mut interval = time::interval(Duration::from_secs(60 * 20));
+            let mut deliveries = vec![];
+            let mut last_parsed = newspapers::LastParsed::default();
+            tracing::info!("Starting to look for new titles");
+    
+            while wait_for_next_cycle(&mut interval, &mut signals, &args, &mut config, &mut regions, &mut http).await {
+                let mut results = newspapers::get_all_checked(&http, &regions, &mut last_parsed).await;
+                record_health(&results);
+                filter_results(&mut results, &config.filter);
+                let mut cycle_runs = vec![];
+    
+                for (newspaper, result, stats) in &results {
+                    let mut new_items = 0;
 
-                    #counters {
-                        display: flex;
-                        gap: 15px;
-                        font-size: 15px;
-                        background-color: rgba(196, 188, 177, 0.288);
-                        border-radius: 5px;
-                        padding: 10px
-                    }
+                    for journal_new in result.iter().flatten() {
+                        let start = Instant::now();
+                        let saved = save_news_to_database(&pool, journal_new).await;
+                        metrics.observe_db_insert(newspaper, start.elapsed());
 
-                    #toolbar {
-                        position: sticky;
-                        top: 0px;
-                        right: 0px;
-                        background-color: rgba(196, 188, 177, 0.288);
-                        margin-bottom: 10px;
-                        width: 100%;
-                        border-radius: 5px;
-                        display: flex;
-                        align-items: center;
-                        justify-content: space-evenly;
-                        padding: 10px 0px;
-                    }
-                    
-                </style>
-                "#;
-
-                let script = r#"
-                <script defer>
-                    const $ = (selector, searchIn) => searchIn ? searchIn.querySelectorAll(selector) : document.querySelectorAll(selector)
-                    const $1 = (selector, searchIn) => searchIn ? searchIn.querySelector(selector) : document.querySelector(selector)
-                    
-                    window.onload = function() {
-                        const hideButtons = $(".hideButton")
-
-                        hideButtons.forEach(button => {
-                            button.hiding = false
-                            
-                            button.addEventListener('click', () => {
-                                const parent = button.parentNode
-
-                                button.hiding = !button.hiding
-                                button.innerText = button.hiding ? '>' : "v"
-
-                                const title = $1("h3", parent)
-                                const text = $1("b", parent)
-
-                                title.style.display = button.hiding ? 'none' : 'block'
-                                text.style.display = button.hiding ? 'none' : 'block'
-                            })
-                        })
-
-                        const rosario3Counter = $1(".rosario3Counter")
-                        const clarinCounter = $1(".clarinCounter")
-                        const infobaeCounter = $1(".infobaeCounter")
-                        const lanacionCounter = $1(".lanacionCounter")
-                        const lacapitalCounter = $1(".lacapitalCounter")
-
-                        let counters = {
-                            rosario3: 0,
-                            clarin: 0,
-                            infobae: 0,
-                            lanacion: 0,
-                            lacapital: 0
+                        if saved {
+                            new_items += 1;
+                            notify_webhooks(&mut deliveries, http.client(), &config, journal_new)
                         }
+                    }
 
-                        $(".news").forEach(news => {
-                            const newspaper = news.className.split(" ")[1]
-                            counters[newspaper] += 1
-                        })
+                    cycle_runs.push(runs::RunRecord::new(newspaper, result, stats, new_items));
+                }
 
-                        rosario3Counter.innerText = counters.rosario3.toString()
-                        clarinCounter.innerText = counters.clarin.toString()
-                        infobaeCounter.innerText = counters.infobae.toString()
-                        lanacionCounter.innerText = counters.lanacion.toString()
-                        lacapitalCounter.innerText = counters.lacapital.toString()
+                cycle_runs.iter().for_each(|run| metrics.record_run(run));
+                save_runs_to_database(&pool, &cycle_runs).await;
+                save_positions_to_database(&pool, &snapshot_positions(&results)).await;
+                save_front_pages_to_database(&pool, &capture_front_pages(&http, &config, &regions).await).await;
+            }
 
-                        const rosario3Checkbox = $1(".rosario3-checkbox")
-                        const clarinCheckbox = $1(".clarin-checkbox")
-                        const infobaeCheckbox = $1(".infobae-checkbox")
-                        const lanacionCheckbox = $1(".lanacion-checkbox")
-                        const lacapitalCheckbox = $1(".lacapital-checkbox")
+            tracing::info!("Shutting down");
+            wait_for_deliveries(deliveries).await;
+            pool.close().await;
+        }).await;
+    } else {
+        if log_format == LogFormat::PRETTY && !quiet {
+            print!("{esc}[2J{esc}[1;1H", esc = 27 as char); // Clear the screen
+        }
 
-                        rosario3Checkbox.addEventListener('click', () => {
+        if let Some(address) = config.server_address.clone() {
+            task::spawn(server::serve(address, config.user.clone(), None));
+        }
 
-                            $(".news.rosario3").forEach(news => {
-                                news.style.display = rosario3Checkbox.checked ? 'block' : 'none'
-                            })
+        let mut signals = signals::listen();
 
-                            $1(".rosario3-title").style.display = rosario3Checkbox.checked ? 'block' : 'none'
-                        })
+        let _ = task::spawn(async move {
+            let mut interval = time::interval(Duration::from_secs(60 * 5));
+            let mut seen_at: HashMap<String, chrono::DateTime<chrono::Utc>> = HashMap::new();
+            // Nothing is stored between runs, the first cycle only learns what is already published
+            let mut first_cycle = true;
+            let mut cycles: usize = 0;
+            let mut deliveries = vec![];
+            let mut last_parsed = newspapers::LastParsed::default();
+
+            while wait_for_next_cycle(&mut interval, &mut signals, &args, &mut config, &mut regions, &mut http).await {
+                let mut results = newspapers::get_all_checked(&http, &regions, &mut last_parsed).await;
+                let source_health = record_health(&results);
+                filter_results(&mut results, &config.filter);
+                let mut cycle_runs = vec![];
+
+                for (newspaper, result, stats) in &results {
+                    let mut new_items = 0;
+
+                    for journal_new in result.iter().flatten() {
+                        if let Entry::Vacant(entry) = seen_at.entry(journal_new.key()) {
+                            entry.insert(chrono::Utc::now());
+
+                            if !first_cycle {
+                                new_items += 1;
+                                notify_webhooks(&mut deliveries, http.client(), &config, journal_new)
+                            }
+                        }
+                    }
 
-                        clarinCheckbox.addEventListener('click', () => {
+                    cycle_runs.push(runs::RunRecord::new(newspaper, result, stats, new_items));
+                }
 
-                            $(".news.clarin").forEach(news => {
-                                news.style.display = clarinCheckbox.checked ? 'block' : 'none'
-                            })
+                let listed: HashSet<String> = results.iter().flat_map(|(_, result, _)| result.iter().flatten()).map(JournalNew::key).collect();
+                let cutoff = chrono::Utc::now() - chrono::Duration::days(SEEN_RETENTION_DAYS);
+                seen_at.retain(|key, seen| *seen > cutoff || listed.contains(key));
+                first_cycle = false;
 
-                            $1(".clarin-title").style.display = clarinCheckbox.checked ? 'block' : 'none'
-                        })
-                        
-                        infobaeCheckbox.addEventListener('click', () => {
+                cycle_runs.iter().for_each(|run| metrics.record_run(run));
+                runs::append(runs::RUNS_FILE, &cycle_runs);
+                positions::append(positions::POSITIONS_FILE, &snapshot_positions(&results));
 
-                            $(".news.infobae").forEach(news => {
-                                news.style.display = infobaeCheckbox.checked ? 'block' : 'none'
-                            })
+                let front_pages = capture_front_pages(&http, &config, &regions).await;
 
-                            $1(".infobae-title").style.display = infobaeCheckbox.checked ? 'block' : 'none'
-                        })
+                if !front_pages.is_empty() {
+                    frontpage::append(frontpage::FRONT_PAGES_FILE, &front_pages);
+                }
 
-                        lanacionCheckbox.addEventListener('click', () => {
+                if cycles.is_multiple_of(ROTATION_CYCLES) {
+                    runs::rotate(runs::RUNS_FILE);
+                    positions::rotate(positions::POSITIONS_FILE);
+                    frontpage::rotate(frontpage::FRONT_PAGES_FILE);
+                }
 
-                            $(".news.lanacion").forEach(news => {
-                                news.style.display = lanacionCheckbox.checked ? 'block' : 'none'
-                            })
+                cycles += 1;
 
-                            $1(".lanacion-title").style.display = lanacionCheckbox.checked ? 'block' : 'none'
-                        })
+                let mut sources: Vec<(Newspaper, Vec<JournalNew>)> = results
+                .into_iter()
+                .map(|(newspaper, result, _)| (newspaper, result.unwrap_or_default()))
+                .collect();
 
-                        lacapitalCheckbox.addEventListener('click', () => {
+                if config.images.cache {
+                    let mut all_news: Vec<&mut JournalNew> = sources.iter_mut().flat_map(|(_, news)| news.iter_mut()).collect();
+                    images::cache_thumbnails(&http, &config.images, &mut all_news).await;
+                }
 
-                            $(".news.lacapital").forEach(news => {
-                                news.style.display = lacapitalCheckbox.checked ? 'block' : 'none'
-                            })
+                let all_news: Vec<&JournalNew> = sources.iter().flat_map(|(_, news)| news).collect();
+                let news_json = serde_json::to_string(&all_news).expect("News should be serializable");
+                files::write_atomically(server::NEWS_FILE, news_json).expect("Error writing news file");
 
-                            $1(".lacapital-title").style.display = lacapitalCheckbox.checked ? 'block' : 'none'
-                        })
+                let combined_feed = feed::format_news_to_atom("all", "Noticias", &all_news, &seen_at);
+                files::write_atomically("jornais.xml", combined_feed).expect("Error writing feed file");
 
-                    }
+                for (newspaper, news) in &sources {
+                    let news: Vec<&JournalNew> = news.iter().collect();
+                    let source_feed = feed::format_news_to_atom(&newspaper.to_string(), &newspaper.info().name, &news, &seen_at);
+                    files::write_atomically(format!("jornais-{newspaper}.xml"), source_feed).expect("Error writing feed file");
+                }
 
+                let html_template = dashboard::format_dashboard(sources, &source_health);
 
-                </script>
-                "#;
-                
-                let html_template = format!(r#"
-                <!DOCTYPE html>
-                <html lang="en">
-                <head>
-                    <meta charset="UTF-8">
-                    <meta name="viewport" content="width=device-width, initial-scale=1.0">
-                    <title>Noticias</title>
-                    {styles}
-                    {script}
-                </head>
-                <body>
-                    <div id="toolbar">
-                        <div>
-                            <input checked class="rosario3-checkbox" type="checkbox">
-                            <span>Rosario3</span>
-                        </div>
-                        <div>
-                            <input checked class="clarin-checkbox" type="checkbox">
-                            <span>Clarin</span>
-                        </div>
-                        <div>
-                            <input checked class="infobae-checkbox" type="checkbox">
-                            <span>Infobae</span>
-                        </div>
-                        <div>
-                            <input checked class="lanacion-checkbox" type="checkbox">
-                            <span>La Nacion</span>
-                        </div>
-                        <div>
-                            <input checked class="lacapital-checkbox" type="checkbox">
-                            <span>La Capital</span>
-                        </div>
-                    </div>
-                    <div id="counters">
-                        <span>Rosario3: <span class="rosario3Counter">0</span> noticias</span>-
-                        <span>Clarin: <span class="clarinCounter">0</span> noticias</span>-
-                        <span>Infobae: <span class="infobaeCounter">0</span> noticias</span>-
-                        <span>La Nacion: <span class="lanacionCounter">0</span> noticias</span>-
-                        <span>La Capital: <span class="lacapitalCounter">0</span> noticias</span>
-                    </div>
-                    {rosario3_html}
-                    {clarin_html}
-                    {infobae_html}
-                    {lanacion_html}
-                    {lacapital_html}
-                </body>
-                </html>
-                "#);
-
-
-                let now = chrono::offset::Local::now();
-
-                let timestamp = format!("[ {} ]", now.format("%d-%m-%Y %H:%M"));
-                std::fs::write("jornais.html", html_template).expect("Error writing HTML file");
-                println!("{} {}\n", timestamp.on_green().black(), "[ HTML FILE UPDATED ]".on_bright_green().black());
+                files::write_atomically("jornais.html", html_template).expect("Error writing HTML file");
+                tracing::info!("HTML file updated");
             }
+
+            tracing::info!("Shutting down");
+            wait_for_deliveries(deliveries).await;
         }).await;
     }
 
@@ -0,0 +1,175 @@
+use std::{collections::HashMap, fmt::Write, sync::Mutex, time::Duration};
+
+use crate::{runs::RunRecord, model::Newspaper};
+
+/// Upper bounds in seconds of the fetch latency buckets
+const FETCH_BUCKETS: &[f64] = &[0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0];
+
+/// Upper bounds in seconds of the database insert latency buckets
+const DB_INSERT_BUCKETS: &[f64] = &[0.001, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 1.0];
+
+#[derive(Debug, Clone, Default)]
+struct Histogram {
+    /// Observations per bucket, not cumulative, the last one counts the ones above every bound
+    buckets: Vec<u64>,
+    sum: f64,
+    count: u64
+}
+
+impl Histogram {
+    fn observe(&mut self, bounds: &[f64], value: f64) {
+        if self.buckets.is_empty() {
+            self.buckets = vec![0; bounds.len() + 1];
+        }
+
+        let bucket = bounds.iter().position(|bound| value <= *bound).unwrap_or(bounds.len());
+        self.buckets[bucket] += 1;
+        self.sum += value;
+        self.count += 1;
+    }
+
+    fn render(&self, output: &mut String, name: &str, source: &str, bounds: &[f64]) {
+        let mut cumulative = 0;
+
+        for (index, bound) in bounds.iter().enumerate() {
+            cumulative += self.buckets.get(index).copied().unwrap_or(0);
+            let _ = writeln!(output, "{name}_bucket{{source=\"{source}\",le=\"{bound}\"}} {cumulative}");
+        }
+
+        let _ = writeln!(output, "{name}_bucket{{source=\"{source}\",le=\"+Inf\"}} {}", self.count);
+        let _ = writeln!(output, "{name}_sum{{source=\"{source}\"}} {}", self.sum);
+        let _ = writeln!(output, "{name}_count{{source=\"{source}\"}} {}", self.count);
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+struct SourceMetrics {
+    fetches: u64,
+    failures: u64,
+    items: u64,
+    new_items: u64,
+    fetch_duration: Histogram,
+    db_insert_duration: Histogram,
+    /// Unix timestamp in seconds of the end of the last fetch without errors
+    last_success: Option<f64>
+}
+
+fn render_counter(output: &mut String, sources: &[(&Newspaper, &SourceMetrics)], name: &str, help: &str, value: fn(&SourceMetrics) -> u64) {
+    let _ = writeln!(output, "# HELP {name} {help}\n# TYPE {name} counter");
+
+    for (newspaper, source) in sources {
+        let _ = writeln!(output, "{name}{{source=\"{newspaper}\"}} {}", value(source));
+    }
+}
+
+/// Counters and histograms per source, rendered in the Prometheus text format
+#[derive(Debug, Default)]
+pub struct Metrics {
+    sources: Mutex<HashMap<Newspaper, SourceMetrics>>
+}
+
+impl Metrics {
+    fn update(&self, newspaper: &Newspaper, change: impl FnOnce(&mut SourceMetrics)) {
+        let mut sources = self.sources.lock().expect("The metrics lock shouldn't be poisoned");
+        change(sources.entry(newspaper.clone()).or_default());
+    }
+
+    pub fn record_run(&self, run: &RunRecord) {
+        let finished_at = chrono::DateTime::parse_from_rfc3339(&run.started_at)
+        .map(|started_at| started_at.timestamp_millis() as f64 / 1000.0)
+        .unwrap_or(chrono::Utc::now().timestamp() as f64)
+            + run.duration_ms as f64 / 1000.0;
+
+        self.update(&run.newspaper, |source| {
+            source.fetches += 1;
+            source.items += run.items as u64;
+            source.new_items += run.new_items as u64;
+            source.fetch_duration.observe(FETCH_BUCKETS, run.duration_ms as f64 / 1000.0);
+
+            if run.error.is_some() {
+                source.failures += 1;
+            } else {
+                source.last_success = Some(finished_at);
+            }
+        });
+    }
+
+    pub fn observe_db_insert(&self, newspaper: &Newspaper, duration: Duration) {
+        self.update(newspaper, |source| source.db_insert_duration.observe(DB_INSERT_BUCKETS, duration.as_secs_f64()));
+    }
+
+    pub fn render(&self) -> String {
+        let sources = self.sources.lock().expect("The metrics lock shouldn't be poisoned");
+        let sources: Vec<(&Newspaper, &SourceMetrics)> = Newspaper::ALL
+        .iter()
+        .filter_map(|newspaper| sources.get_key_value(newspaper))
+        .collect();
+
+        let mut output = String::new();
+
+        render_counter(&mut output, &sources, "jornais_fetches_total", "Fetches of the source", |source| source.fetches);
+        render_counter(&mut output, &sources, "jornais_fetch_failures_total", "Fetches of the source that ended in an error", |source| source.failures);
+        render_counter(&mut output, &sources, "jornais_items_parsed_total", "Stories parsed from the source", |source| source.items);
+        render_counter(&mut output, &sources, "jornais_new_items_total", "Stories of the source that weren't seen before", |source| source.new_items);
+
+        let name = "jornais_fetch_duration_seconds";
+        let _ = writeln!(output, "# HELP {name} Time taken to fetch and parse the source\n# TYPE {name} histogram");
+
+        for (newspaper, source) in &sources {
+            source.fetch_duration.render(&mut output, name, &newspaper.to_string(), FETCH_BUCKETS);
+        }
+
+        let name = "jornais_db_insert_duration_seconds";
+        let _ = writeln!(output, "# HELP {name} Time taken to save a story of the source in the database\n# TYPE {name} histogram");
+
+        for (newspaper, source) in &sources {
+            source.db_insert_duration.render(&mut output, name, &newspaper.to_string(), DB_INSERT_BUCKETS);
+        }
+
+        let name = "jornais_last_success_timestamp_seconds";
+        let _ = writeln!(output, "# HELP {name} Unix time of the last fetch of the source without errors\n# TYPE {name} gauge");
+
+        for (newspaper, source) in &sources {
+            if let Some(last_success) = source.last_success {
+                let _ = writeln!(output, "{name}{{source=\"{newspaper}\"}} {last_success}");
+            }
+        }
+
+        output
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{runs::FetchStats, testing::sample_new};
+
+    #[test]
+    fn render_per_source() {
+        let metrics = Metrics::default();
+        let stats = FetchStats {
+            started_at: String::from("2024-05-01T10:00:00+00:00"),
+            duration_ms: 700,
+            http_status: Some(200),
+            bytes: 1024,
+            ..FetchStats::default()
+        };
+
+        metrics.record_run(&RunRecord::new(&Newspaper::CLARIN, &Ok(vec![sample_new()]), &stats, 1));
+        metrics.record_run(&RunRecord::new(&Newspaper::CLARIN, &Err(String::from("timeout")), &stats, 0));
+        metrics.observe_db_insert(&Newspaper::CLARIN, Duration::from_millis(3));
+
+        let output = metrics.render();
+
+        assert!(output.contains("# TYPE jornais_fetches_total counter"));
+        assert!(output.contains("jornais_fetches_total{source=\"clarin\"} 2"));
+        assert!(output.contains("jornais_fetch_failures_total{source=\"clarin\"} 1"));
+        assert!(output.contains("jornais_items_parsed_total{source=\"clarin\"} 1"));
+        assert!(output.contains("jornais_new_items_total{source=\"clarin\"} 1"));
+        assert!(output.contains("jornais_fetch_duration_seconds_bucket{source=\"clarin\",le=\"0.5\"} 0"));
+        assert!(output.contains("jornais_fetch_duration_seconds_bucket{source=\"clarin\",le=\"1\"} 2"));
+        assert!(output.contains("jornais_db_insert_duration_seconds_bucket{source=\"clarin\",le=\"0.005\"} 1"));
+        assert!(output.contains("jornais_last_success_timestamp_seconds{source=\"clarin\"} 1714557600.7"));
+        assert!(!output.contains("source=\"tn\""));
+    }
+}
@@ -1,34 +1,382 @@
-use std::fmt::Display;
+use std::{fmt::Display, str::FromStr};
 
 use serde::{Serialize, Deserialize};
 
-#[derive(Debug, Clone, Eq, PartialEq)]
+#[derive(Debug, Clone, Eq, PartialEq, Hash, Serialize, Deserialize)]
+#[serde(into = "String", try_from = "String")]
 pub enum Newspaper {
     CLARIN,
     LANACION,
     ROSARIO3,
     INFOBAE,
-    LACAPITAL
+    LACAPITAL,
+    PAGINA12,
+    AMBITO,
+    PERFIL,
+    TN,
+    LAVOZ,
+    LOSANDES,
+    ELLITORAL,
+    RIONEGRO
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Region {
+    NACIONAL,
+    SANTAFE,
+    CORDOBA,
+    MENDOZA,
+    RIONEGRO
+}
+
+impl Region {
+    pub const ALL: [Region; 5] = [Region::NACIONAL, Region::SANTAFE, Region::CORDOBA, Region::MENDOZA, Region::RIONEGRO];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::NACIONAL => "Nacional",
+            Self::SANTAFE => "Santa Fe",
+            Self::CORDOBA => "Córdoba",
+            Self::MENDOZA => "Mendoza",
+            Self::RIONEGRO => "Río Negro"
+        }
+    }
+}
+
+impl Display for Region {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NACIONAL => write!(f, "nacional"),
+            Self::SANTAFE => write!(f, "santafe"),
+            Self::CORDOBA => write!(f, "cordoba"),
+            Self::MENDOZA => write!(f, "mendoza"),
+            Self::RIONEGRO => write!(f, "rionegro")
+        }
+    }
+}
+
+impl FromStr for Region {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Region::ALL
+        .into_iter()
+        .find(|region| region.to_string() == s.trim().to_lowercase())
+        .ok_or(format!("Unknown region {s}, expected one of nacional, santafe, cordoba, mendoza or rionegro"))
+    }
+}
+
+/// Everything known about a source, `id` is stable and used for storage, class names and URLs
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct SourceInfo {
+    pub id: String,
+    pub name: String,
+    pub home_url: String,
+    pub country: String,
+    pub region: Region,
+    pub language: String,
+    /// Editorial line the source is usually described with, `None` when there isn't a settled one
+    pub lean: Option<String>,
+    #[serde(default)]
+    pub tags: Vec<String>
+}
+
+impl SourceInfo {
+    fn argentinian(id: &str, name: &str, home_url: &str, region: Region, lean: Option<&str>, tags: &[&str]) -> SourceInfo {
+        SourceInfo {
+            id: String::from(id),
+            name: String::from(name),
+            home_url: String::from(home_url),
+            country: String::from("AR"),
+            region,
+            language: String::from("es"),
+            lean: lean.map(String::from),
+            tags: tags.iter().map(|tag| String::from(*tag)).collect()
+        }
+    }
+}
+
+impl FromStr for SourceInfo {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.parse::<Newspaper>().map(|newspaper| newspaper.info())
+    }
+}
+
+impl Newspaper {
+    pub const ALL: [Newspaper; 13] = [
+        Newspaper::CLARIN,
+        Newspaper::INFOBAE,
+        Newspaper::LANACION,
+        Newspaper::LACAPITAL,
+        Newspaper::ROSARIO3,
+        Newspaper::PAGINA12,
+        Newspaper::AMBITO,
+        Newspaper::PERFIL,
+        Newspaper::TN,
+        Newspaper::LAVOZ,
+        Newspaper::LOSANDES,
+        Newspaper::ELLITORAL,
+        Newspaper::RIONEGRO
+    ];
+
+    /// The leans are the project's own editorial reading of each outlet, they don't come from an external
+    /// rating. Only the national outlets get one and a change to them should be argued in its pull request
+    pub fn info(&self) -> SourceInfo {
+        match self {
+            Self::CLARIN => SourceInfo::argentinian("clarin", "Clarin", "https://www.clarin.com", Region::NACIONAL, Some("centro-derecha"), &[]),
+            Self::LANACION => SourceInfo::argentinian("lanacion", "La Nacion", "https://www.lanacion.com.ar", Region::NACIONAL, Some("derecha"), &[]),
+            Self::ROSARIO3 => SourceInfo::argentinian("rosario3", "Rosario3", "https://www.rosario3.com", Region::SANTAFE, None, &["rosario"]),
+            Self::INFOBAE => SourceInfo::argentinian("infobae", "Infobae", "https://www.infobae.com", Region::NACIONAL, Some("centro-derecha"), &["digital"]),
+            Self::LACAPITAL => SourceInfo::argentinian("lacapital", "La Capital", "https://www.lacapital.com.ar", Region::SANTAFE, None, &["rosario"]),
+            Self::PAGINA12 => SourceInfo::argentinian("pagina12", "Página/12", "https://www.pagina12.com.ar", Region::NACIONAL, Some("izquierda"), &[]),
+            Self::AMBITO => SourceInfo::argentinian("ambito", "Ámbito Financiero", "https://www.ambito.com", Region::NACIONAL, Some("centro"), &["economia"]),
+            Self::PERFIL => SourceInfo::argentinian("perfil", "Perfil", "https://www.perfil.com", Region::NACIONAL, Some("centro"), &[]),
+            Self::TN => SourceInfo::argentinian("tn", "Todo Noticias", "https://tn.com.ar", Region::NACIONAL, Some("centro-derecha"), &["television"]),
+            Self::LAVOZ => SourceInfo::argentinian("lavoz", "La Voz del Interior", "https://www.lavoz.com.ar", Region::CORDOBA, None, &[]),
+            Self::LOSANDES => SourceInfo::argentinian("losandes", "Los Andes", "https://www.losandes.com.ar", Region::MENDOZA, None, &[]),
+            Self::ELLITORAL => SourceInfo::argentinian("ellitoral", "El Litoral", "https://www.ellitoral.com", Region::SANTAFE, None, &["santa fe"]),
+            Self::RIONEGRO => SourceInfo::argentinian("rionegro", "Río Negro", "https://www.rionegro.com.ar", Region::RIONEGRO, None, &["patagonia"])
+        }
+    }
+
+    pub fn region(&self) -> Region {
+        self.info().region
+    }
+
+    /// An empty list of regions means every region
+    pub fn in_regions(&self, regions: &[Region]) -> bool {
+        regions.is_empty() || regions.contains(&self.region())
+    }
 }
 
 impl Display for Newspaper {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.info().id)
+    }
+}
+
+impl FromStr for Newspaper {
+    type Err = String;
+
+    /// Also accepts the names older versions stored in the database, like "la nacion"
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let id = s.trim().to_lowercase().replace(' ', "");
+
+        Newspaper::ALL
+        .into_iter()
+        .find(|newspaper| newspaper.to_string() == id)
+        .ok_or(format!("Unknown newspaper {s}"))
+    }
+}
+
+impl From<Newspaper> for String {
+    fn from(newspaper: Newspaper) -> Self {
+        newspaper.to_string()
+    }
+}
+
+impl TryFrom<String> for Newspaper {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+/// What a story is, as marked in the listing of the source
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ContentKind {
+    #[default]
+    ARTICLE,
+    /// Live blogs that keep being updated
+    LIVE,
+    VIDEO,
+    GALLERY,
+    /// Paid notes written for a brand
+    SPONSORED
+}
+
+impl ContentKind {
+    pub const ALL: [ContentKind; 5] = [ContentKind::ARTICLE, ContentKind::LIVE, ContentKind::VIDEO, ContentKind::GALLERY, ContentKind::SPONSORED];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::ARTICLE => "Nota",
+            Self::LIVE => "En vivo",
+            Self::VIDEO => "Video",
+            Self::GALLERY => "Galería",
+            Self::SPONSORED => "Patrocinado"
+        }
+    }
+}
+
+impl Display for ContentKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::ARTICLE => write!(f, "article"),
+            Self::LIVE => write!(f, "live"),
+            Self::VIDEO => write!(f, "video"),
+            Self::GALLERY => write!(f, "gallery"),
+            Self::SPONSORED => write!(f, "sponsored")
+        }
+    }
+}
+
+impl FromStr for ContentKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        ContentKind::ALL
+        .into_iter()
+        .find(|kind| kind.to_string() == s.trim().to_lowercase())
+        .ok_or(format!("Unknown content kind {s}, expected one of article, live, video, gallery or sponsored"))
+    }
+}
+
+/// Shared taxonomy the sections of every source are mapped to
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Section {
+    POLITICS,
+    ECONOMY,
+    SPORTS,
+    POLICE,
+    SOCIETY,
+    WORLD,
+    ENTERTAINMENT,
+    CULTURE,
+    TECHNOLOGY,
+    HEALTH,
+    OPINION,
+    /// The source didn't say or it isn't in the taxonomy
+    #[default]
+    OTHER
+}
+
+impl Section {
+    pub const ALL: [Section; 12] = [
+        Section::POLITICS,
+        Section::ECONOMY,
+        Section::SPORTS,
+        Section::POLICE,
+        Section::SOCIETY,
+        Section::WORLD,
+        Section::ENTERTAINMENT,
+        Section::CULTURE,
+        Section::TECHNOLOGY,
+        Section::HEALTH,
+        Section::OPINION,
+        Section::OTHER
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::POLITICS => "Política",
+            Self::ECONOMY => "Economía",
+            Self::SPORTS => "Deportes",
+            Self::POLICE => "Policiales",
+            Self::SOCIETY => "Sociedad",
+            Self::WORLD => "El mundo",
+            Self::ENTERTAINMENT => "Espectáculos",
+            Self::CULTURE => "Cultura",
+            Self::TECHNOLOGY => "Tecnología",
+            Self::HEALTH => "Salud",
+            Self::OPINION => "Opinión",
+            Self::OTHER => "Otras"
+        }
+    }
+}
+
+impl Display for Section {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            Self::CLARIN => write!(f, "clarin"),
-            Self::LANACION => write!(f, "lanacion"),
-            Self::ROSARIO3 => write!(f, "rosario3"),
-            Self::INFOBAE => write!(f, "infobae"),
-            Self::LACAPITAL => write!(f, "lacapital")
+            Self::POLITICS => write!(f, "politics"),
+            Self::ECONOMY => write!(f, "economy"),
+            Self::SPORTS => write!(f, "sports"),
+            Self::POLICE => write!(f, "police"),
+            Self::SOCIETY => write!(f, "society"),
+            Self::WORLD => write!(f, "world"),
+            Self::ENTERTAINMENT => write!(f, "entertainment"),
+            Self::CULTURE => write!(f, "culture"),
+            Self::TECHNOLOGY => write!(f, "technology"),
+            Self::HEALTH => write!(f, "health"),
+            Self::OPINION => write!(f, "opinion"),
+            Self::OTHER => write!(f, "other")
         }
     }
 }
 
-#[derive(Debug, Eq, PartialEq, Clone)]
+impl FromStr for Section {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Section::ALL
+        .into_iter()
+        .find(|section| section.to_string() == s.trim().to_lowercase())
+        .ok_or(format!("Unknown section {s}, expected one of {}", Section::ALL.map(|section| section.to_string()).join(", ")))
+    }
+}
+
+/// Main picture of a story as found in the listing
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct LeadImage {
+    pub url: String,
+    pub alt: Option<String>,
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+    /// File name of the resized copy in `images::THUMBNAILS_DIR`, when images are cached
+    #[serde(default)]
+    pub thumbnail: Option<String>
+}
+
+#[derive(Debug, Eq, PartialEq, Clone, Serialize, Deserialize)]
 pub struct JournalNew {
     pub title: String,
     pub text: String,
     pub link: Option<String>,
-    pub newspaper: Newspaper
+    pub newspaper: Newspaper,
+    #[serde(default)]
+    pub kind: ContentKind,
+    /// Only readable with a subscription
+    #[serde(default)]
+    pub paywalled: bool,
+    #[serde(default)]
+    pub section: Section,
+    /// Names in the byline, see `authors::parse`
+    #[serde(default)]
+    pub authors: Vec<String>,
+    #[serde(default)]
+    pub image: Option<LeadImage>,
+    /// Place of the story in the listing, 1 for the top one
+    #[serde(default)]
+    pub position: usize,
+    /// Block of the page the story was in, like `apertura` or `destacadas`, when the source names them
+    #[serde(default)]
+    pub block: Option<String>
+}
+
+impl JournalNew {
+    /// Identifies the story across fetches, the link when there is one and the title otherwise
+    pub fn key(&self) -> String {
+        self.link.clone().unwrap_or(self.title.clone())
+    }
+
+    /// Cleans the scraped title, text and image alt, see `text::clean`
+    pub fn normalized(self) -> JournalNew {
+        JournalNew {
+            title: crate::text::clean(&self.title),
+            text: crate::text::clean(&self.text),
+            image: self.image.map(|image| LeadImage {
+                alt: image.alt.map(|alt| crate::text::clean(&alt)).filter(|alt| !alt.is_empty()),
+                ..image
+            }),
+            ..self
+        }
+    }
 }
 
 #[derive(Serialize, Deserialize)]
@@ -37,4 +385,37 @@ pub struct DBInfo {
     pub password: String,
     pub name: String,
     pub port: String
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn regions_filter_newspapers() {
+        assert_eq!("SantaFe".parse::<Region>(), Ok(Region::SANTAFE));
+        assert!("patagonia".parse::<Region>().is_err());
+
+        let santa_fe: Vec<Newspaper> = Newspaper::ALL.into_iter().filter(|newspaper| newspaper.in_regions(&[Region::SANTAFE])).collect();
+
+        assert_eq!(santa_fe, vec![Newspaper::LACAPITAL, Newspaper::ROSARIO3, Newspaper::ELLITORAL]);
+        assert!(Newspaper::ALL.iter().all(|newspaper| newspaper.in_regions(&[])));
+    }
+
+    #[test]
+    fn source_info_round_trips() {
+        for newspaper in Newspaper::ALL {
+            let id = newspaper.to_string();
+
+            assert_eq!(id.parse::<Newspaper>(), Ok(newspaper.clone()));
+            assert_eq!(serde_json::to_string(&newspaper).unwrap(), format!("\"{id}\""));
+            assert_eq!(serde_json::from_str::<Newspaper>(&format!("\"{id}\"")).unwrap(), newspaper);
+            assert_eq!(id.parse::<SourceInfo>(), Ok(newspaper.info()));
+
+            let info = newspaper.info();
+            assert_eq!(serde_json::from_str::<SourceInfo>(&serde_json::to_string(&info).unwrap()).unwrap(), info);
+        }
+
+        assert_eq!("la nacion".parse::<Newspaper>(), Ok(Newspaper::LANACION));
+        assert!("el diario".parse::<Newspaper>().is_err());
+    }
+}
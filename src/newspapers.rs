@@ -1,234 +1,1125 @@
-use tl::{VDom, Node, Parser, HTMLTag};
+use std::{collections::HashMap, time::Instant};
 
-use crate::model::{JournalNew, Newspaper};
+use tl::{VDom, Node, NodeHandle, Parser, HTMLTag};
+use tracing::Instrument;
 
-fn get_elements<'a>(selector: &str, dom: &VDom<'a>, parser: &Parser<'a>) -> Vec<Node<'a>> {
-    let selected = dom
-    .query_selector(selector)
-    .expect("The query selector should be valid and exist");
+use crate::{archive, authors, classify, frontpage::{FrontPage, FrontPageBlock}, http::HttpClient, runs::FetchStats, model::{JournalNew, LeadImage, Newspaper, Region}};
 
-    let mut result: Vec<Node> = vec![];
+/// Outcome of fetching a source together with the stats of the fetch
+pub type SourceFetch = (Newspaper, Result<Vec<JournalNew>, String>, FetchStats);
 
-    selected.for_each(|element| {
-        let node = element.get(parser).expect("The element should be gettable from the parser");
+/// Listing page of each source, its links are resolved against it
+const CLARIN_URL: &str = "https://www.clarin.com/ultimo-momento/";
+const INFOBAE_URL: &str = "https://www.infobae.com/ultimas-noticias-america/";
+const LANACION_URL: &str = "https://www.lanacion.com.ar/ultimas-noticias/";
+const LACAPITAL_URL: &str = "https://www.lacapital.com.ar/secciones/ultimo-momento.html";
+const ROSARIO3_URL: &str = "https://www.rosario3.com/seccion/ultimas-noticias/";
+const PAGINA12_URL: &str = "https://www.pagina12.com.ar/ultimas-noticias";
+const AMBITO_URL: &str = "https://www.ambito.com/ultimas-noticias";
+const PERFIL_URL: &str = "https://www.perfil.com/ultimo-momento";
+const TN_URL: &str = "https://tn.com.ar/ultimas-noticias/";
+const LAVOZ_URL: &str = "https://www.lavoz.com.ar/ultimas-noticias/";
+const LOSANDES_URL: &str = "https://www.losandes.com.ar/ultimas-noticias/";
+const ELLITORAL_URL: &str = "https://www.ellitoral.com/ultimas-noticias";
+const RIONEGRO_URL: &str = "https://www.rionegro.com.ar/ultimas-noticias/";
 
-        result.push(node.clone());
-    });
+/// A listing as `fetch_page` got it
+#[derive(Debug, Clone, PartialEq)]
+pub enum Fetched {
+    Page(String),
+    /// The listing didn't change since the last fetch, the news parsed from it then are still current
+    NotModified
+}
 
-    result
+/// The stories a scraper got from a listing page and how many it skipped for missing a field
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Listing {
+    pub news: Vec<JournalNew>,
+    pub missing_titles: usize,
+    pub missing_links: usize
+}
+
+/// The listing last parsed from each source, reused while it doesn't change.
+/// Kept by whoever fetches the sources cycle after cycle and passed to `get_all_checked`.
+#[derive(Debug, Clone, Default)]
+pub struct LastParsed {
+    listings: HashMap<Newspaper, Listing>
+}
+
+impl LastParsed {
+    pub fn get(&self, newspaper: &Newspaper) -> Option<&Listing> {
+        self.listings.get(newspaper)
+    }
+
+    pub fn insert(&mut self, newspaper: Newspaper, listing: Listing) {
+        self.listings.insert(newspaper, listing);
+    }
+}
+
+fn get_elements<'a>(selector: &str, dom: &VDom<'a>, parser: &Parser<'a>) -> Vec<Node<'a>> {
+    match dom.query_selector(selector) {
+        Some(selected) => selected.filter_map(|element| element.get(parser).cloned()).collect(),
+        None => vec![]
+    }
 }
 
-fn query_node<'a>(selector: &str, node_tag: &HTMLTag, parser: &Parser<'a>) -> Node<'a> {
-    let select = node_tag.query_selector(parser, selector).expect("h2 should exist inside article").next().unwrap();
+/// The first element inside a tag matching `selector`, `None` when nothing matches.
+/// Descendant selectors like `h2.title a` are resolved one step at a time, tl doesn't match them inside a tag.
+fn try_query_node<'a>(selector: &str, node_tag: &HTMLTag, parser: &Parser<'a>) -> Option<Node<'a>> {
+    let mut steps = selector.split_whitespace();
+    let mut node = node_tag.query_selector(parser, steps.next()?)?.next()?.get(parser).cloned()?;
 
-    select.get(parser).unwrap().clone()
+    for step in steps {
+        node = node.as_tag()?.query_selector(parser, step)?.next()?.get(parser).cloned()?;
+    }
 
+    Some(node)
 }
 
-fn get_attribute(attribute: &str, node: &Node) -> String {
-    let node_tag = node.as_tag().unwrap();
-    String::from(node_tag.attributes().get(attribute).expect("Failed to get attribute for a tag").unwrap().as_utf8_str())
+fn try_get_attribute(attribute: &str, node: &Node) -> Option<String> {
+    Some(String::from(node.as_tag()?.attributes().get(attribute)??.as_utf8_str()))
 }
 
-pub async fn get_clarin() -> Vec<JournalNew> {
-    let first_page_load = reqwest::get("https://www.clarin.com/ultimo-momento/").await;
-    let mut latest_news: Vec<JournalNew> = vec![];
+/// What a `data-` attribute says about a story: the name of a flag set to a truthy value like `data-live="true"`,
+/// nothing for a negative one like `data-paywall="false"` and otherwise only the value, like `video` in `data-type="video"`
+fn data_marker(name: &str, value: &str) -> Option<String> {
+    match value.trim().to_lowercase().as_str() {
+        "" | "false" | "0" | "no" => None,
+        "true" | "1" | "yes" => Some(String::from(name)),
+        value => Some(String::from(value))
+    }
+}
 
-    match first_page_load {
-        Ok(response) => {
-            let response_html = response.text().await.unwrap();
+/// Class names and `data-` attributes of a story and everything inside it, where the sources mark paywalls and kinds.
+/// Values that look like URLs are left out so image and video addresses don't count.
+fn story_markers(node: &Node, parser: &Parser) -> Vec<String> {
+    let descendants = node.children().map(|children| children.all(parser)).unwrap_or_default();
 
-            let dom = tl::parse(&response_html, tl::ParserOptions::default()).unwrap();
-            let parser = dom.parser();
-            let articles = get_elements("article", &dom, parser);
+    std::iter::once(node)
+    .chain(descendants)
+    .filter_map(|node| node.as_tag())
+    .flat_map(|tag| tag.attributes().iter().filter_map(|(name, value)| {
+        let value = value.unwrap_or_default();
 
-            articles.iter().for_each(|node| {
-                let node_tag = node.as_tag().unwrap();
+        match name.as_ref() {
+            "class" => Some(String::from(value)),
+            name if name.starts_with("data-") && !value.contains('/') => data_marker(name, &value),
+            _ => None
+        }
+    }).collect::<Vec<String>>())
+    .collect()
+}
 
-                let h2 = query_node("h2", node_tag, parser);
-                let h3 = query_node("h3.summary", node_tag, parser);
-                let a = query_node("a.link-new", node_tag, parser);
-                let link = get_attribute("href", &a);
+/// Texts of the section labels and breadcrumbs of a story together with its `data-section` and `data-category` values
+fn story_sections(node: &Node, parser: &Parser) -> Vec<String> {
+    let descendants = node.children().map(|children| children.all(parser)).unwrap_or_default();
 
-                if latest_news.iter().any(|journal_new| journal_new.link == Some(String::from(link.clone()))) {
-                    return
-                }
-                
-                latest_news.push(
-                    JournalNew { 
-                        title: String::from(h2.inner_text(parser)), 
-                        text: String::from(h3.inner_text(parser)), 
-                        link: Some(String::from(link)),
-                        newspaper: Newspaper::CLARIN
-                    });
+    std::iter::once(node)
+    .chain(descendants)
+    .filter_map(|node| node.as_tag())
+    .flat_map(|tag| {
+        let attributes = tag.attributes();
+        let mut labels: Vec<String> = ["data-section", "data-category", "data-seccion"]
+        .iter()
+        .filter_map(|name| attributes.get(*name).flatten())
+        .map(|value| String::from(value.as_utf8_str()))
+        .collect();
+
+        let class = attributes.class().map(|class| class.as_utf8_str().to_lowercase()).unwrap_or_default();
 
-            });
+        if class.split_whitespace().any(|token| SECTION_LABEL_CLASSES.contains(&token)) {
+            labels.push(label_text(tag, parser));
+        }
 
+        labels
+    })
+    .collect()
+}
+
+/// Class names of the elements holding the section of a story, like a kicker or a breadcrumb
+const SECTION_LABEL_CLASSES: [&str; 7] = ["section", "seccion", "section-name", "category", "categoria", "breadcrumb", "breadcrumbs"];
+
+/// Text of a label element, made of its own text and the text of its links and spans, like the items of a breadcrumb.
+/// Anything else inside, like a heading or a summary, is left out
+fn label_text(tag: &HTMLTag, parser: &Parser) -> String {
+    tag.children()
+    .top()
+    .iter()
+    .filter_map(|handle| handle.get(parser))
+    .filter_map(|node| match node {
+        Node::Raw(text) => Some(String::from(text.as_utf8_str())),
+        Node::Tag(child) if ["a", "span", "li"].contains(&&*child.name().as_utf8_str()) => Some(String::from(child.inner_text(parser))),
+        _ => None
+    })
+    .map(|piece| String::from(piece.trim()))
+    .filter(|piece| piece.chars().any(char::is_alphanumeric))
+    .collect::<Vec<String>>()
+    .join(" › ")
+}
+
+/// Name of the block or slot the source placed the story in, from its `data-` attributes
+fn story_block(node: &Node) -> Option<String> {
+    let attributes = node.as_tag()?.attributes();
+
+    ["data-block", "data-slot", "data-zone", "data-module"]
+    .iter()
+    .filter_map(|name| attributes.get(*name).flatten())
+    .map(|value| crate::text::clean(&value.as_utf8_str()))
+    .find(|value| !value.is_empty())
+}
 
+/// Elements of a front page that group stories, `<section>` or anything with a block attribute
+fn is_front_page_block(tag: &HTMLTag) -> bool {
+    tag.name().as_utf8_str() == "section"
+    || ["data-block", "data-slot", "data-zone", "data-module"].iter().any(|name| tag.attributes().get(*name).flatten().is_some())
+}
+
+/// Walks the document once from `handle`, noting the name of the innermost block around each article, keyed by its node
+fn find_article_blocks(handle: NodeHandle, parser: &Parser, block: &Option<String>, article_blocks: &mut HashMap<u32, Option<String>>) {
+    let Some(node) = handle.get(parser) else { return };
+    let Some(tag) = node.as_tag() else { return };
+
+    let inner_block;
+    let block = match is_front_page_block(tag) {
+        true => {
+            inner_block = front_page_block_name(node);
+            &inner_block
         },
-        Err(error) => println!("{}", error)
+        false => block
+    };
+
+    if tag.name().as_utf8_str() == "article" {
+        article_blocks.insert(handle.get_inner(), block.clone());
     }
 
-    latest_news
+    for child in tag.children().top().iter() {
+        find_article_blocks(*child, parser, block, article_blocks);
+    }
+}
+
+/// Name of a block of a front page, from its `data-` attributes, `aria-label`, `id` or first class name
+fn front_page_block_name(node: &Node) -> Option<String> {
+    let attributes = node.as_tag()?.attributes();
+
+    story_block(node)
+    .or_else(|| ["aria-label", "id"]
+        .iter()
+        .filter_map(|name| attributes.get(*name).flatten())
+        .map(|value| crate::text::clean(&value.as_utf8_str()))
+        .find(|value| !value.is_empty()))
+    .or_else(|| attributes.class()?.as_utf8_str().split_whitespace().next().map(String::from))
 }
 
-pub async fn get_infobae() -> Vec<JournalNew> {
-    let first_page_load = reqwest::get("https://www.infobae.com/ultimas-noticias-america/").await;
-    let mut latest_news: Vec<JournalNew> = vec![];
+/// Texts of the bylines of a story, from the elements marked as the author with their class, `rel` or `itemprop`,
+/// together with its `data-author` values
+fn story_bylines(node: &Node, parser: &Parser) -> Vec<String> {
+    let descendants = node.children().map(|children| children.all(parser)).unwrap_or_default();
 
-    match first_page_load {
-        Ok(response) => {
-            let response_html = response.text().await.unwrap();
+    std::iter::once(node)
+    .chain(descendants)
+    .filter_map(|node| node.as_tag())
+    .flat_map(|tag| {
+        let attributes = tag.attributes();
+        let attribute = |name: &str| attributes.get(name).flatten().map(|value| value.as_utf8_str().to_lowercase()).unwrap_or_default();
+        let mut bylines: Vec<String> = attributes.get("data-author").flatten().map(|value| String::from(value.as_utf8_str())).into_iter().collect();
 
-            let dom = tl::parse(&response_html, tl::ParserOptions::default()).unwrap();
-            let parser = dom.parser();
+        let marked = attribute("rel") == "author"
+        || attribute("itemprop") == "author"
+        || ["author", "autor", "byline", "firma"].iter().any(|marker| attribute("class").contains(marker));
 
-            let a_tags = get_elements("a.feed-list-card", &dom, parser);
+        if marked {
+            bylines.push(String::from(tag.inner_text(parser)));
+        }
 
-            a_tags.iter().for_each(|node| {
-                let node_tag = node.as_tag().unwrap();
+        bylines
+    })
+    .collect()
+}
 
-                let title = query_node("h2.feed-list-card-headline-lean", node_tag, parser);
-                let text = query_node("div.deck", node_tag, parser);
+/// The first image of a story that isn't a tracking pixel, preferring the lazy loading attributes over a placeholder `src`
+fn story_image(node: &Node, parser: &Parser, page: &str) -> Option<LeadImage> {
+    let descendants = node.children().map(|children| children.all(parser)).unwrap_or_default();
 
-                let link = get_attribute("href", node);
+    std::iter::once(node)
+    .chain(descendants)
+    .filter_map(|node| node.as_tag())
+    .filter(|tag| tag.name().as_utf8_str() == "img")
+    .find_map(|tag| {
+        let attributes = tag.attributes();
+        let attribute = |name: &str| attributes.get(name).flatten().map(|value| String::from(value.as_utf8_str().trim()));
+        let dimension = |name: &str| attribute(name).and_then(|value| value.trim_end_matches("px").parse::<u32>().ok());
 
-                if latest_news.iter().any(|journal_new| journal_new.link == Some(format!("http://infobae.com{}", link.clone()))) {
-                    return
-                }
+        let source = ["data-src", "data-lazy-src", "data-original", "src"]
+        .iter()
+        .filter_map(|name| attribute(name))
+        .chain(["data-srcset", "srcset"].iter().filter_map(|name| attribute(name)?.split_whitespace().next().map(String::from)))
+        // Only http(s) sources end up in the markup, placeholders like `data:` or `javascript:` are skipped
+        .find_map(|source| resolve_link(page, &source))?;
 
-                let journal_new = JournalNew {
-                    title: String::from(title.inner_text(parser)),
-                    text: String::from(text.inner_text(parser)),
-                    link: Some(format!("http://infobae.com{}", link)),
-                    newspaper: Newspaper::INFOBAE
-                };
+        let (width, height) = (dimension("width"), dimension("height"));
 
-                latest_news.push(journal_new)
-            })
-        },
-        Err(error) => println!("{}", error)
-    } 
+        if width.is_some_and(|width| width <= 1) || height.is_some_and(|height| height <= 1) {
+            return None
+        }
 
-    latest_news
+        Some(LeadImage {
+            url: source,
+            alt: attribute("alt"),
+            width,
+            height,
+            thumbnail: None
+        })
+    })
 }
 
-pub async fn get_lanacion() -> Vec<JournalNew> {
-    let first_page_load = reqwest::get("https://www.lanacion.com.ar/ultimas-noticias/").await;
-    let mut latest_news: Vec<JournalNew> = vec![];
+/// Resolves the href of a story against the `page` it was found in, upgrading it to https.
+/// Returns `None` for anything that isn't a link to a web page, like `javascript:` or `mailto:` hrefs.
+pub fn resolve_link(page: &str, href: &str) -> Option<String> {
+    let href = crate::text::decode_entities(href.trim());
 
-    match first_page_load {
-        Ok(response) => {
-            let response_html = response.text().await.unwrap();
+    if href.is_empty() || href.starts_with('#') {
+        return None
+    }
 
-            let dom = tl::parse(&response_html, tl::ParserOptions::default()).unwrap();
-            let parser = dom.parser();
+    let mut link = reqwest::Url::parse(page).ok()?.join(&href).ok()?;
 
-            let article_tags = get_elements("article.mod-article", &dom, parser);
+    match link.scheme() {
+        "https" => {},
+        "http" => link.set_scheme("https").ok()?,
+        _ => return None
+    }
 
-            article_tags.iter().for_each(|node| {
-                let node_tag = node.as_tag().unwrap();
+    link.host_str()?;
+    link.set_fragment(None);
 
+    Some(String::from(link))
+}
 
-                let a_tag = query_node("a.com-link", node_tag, parser);
+/// Gets a listing of `newspaper` with the shared client, adding its status and size to the `stats` of the run of the source.
+/// When the listing didn't change and `can_reuse` the previous result it returns `Fetched::NotModified`,
+/// otherwise the cached body comes back as a page. Listings that did change are archived under `newspaper` when the client has an `archive_dir`.
+async fn fetch_page(client: &HttpClient, newspaper: &Newspaper, url: &str, can_reuse: bool, stats: &mut FetchStats) -> Result<Fetched, String> {
+    let page = client.get(url).await;
 
-                let title = a_tag.inner_text(parser);
+    if let (Some(directory), Ok(page)) = (&client.config().archive_dir, &page) {
+        if !page.not_modified {
+            if let Err(error) = archive::store(directory, newspaper, url, page.status, &page.headers, &page.raw) {
+                tracing::warn!(url, %error, "Couldn't archive the page");
+            }
+        }
+    }
 
-                let link = get_attribute("href", &a_tag);
+    if let Ok(page) = &page {
+        stats.http_status = Some(page.status);
 
-                if latest_news.iter().any(|journal_new| journal_new.link == Some(format!("http://lanacion.com{}", link.clone()))) {
-                    return
-                }
+        if !page.not_modified {
+            stats.bytes += page.raw.len();
+        }
+    }
 
-                latest_news.push(
-                    JournalNew { 
-                        title: String::from(title), 
-                        text: String::from(""), 
-                        link: Some(format!("http://lanacion.com{}", link)),
-                        newspaper: Newspaper::LANACION
-                    });
-            })
-        },
-        Err(error) => println!("{}", error)
+    let result = match page {
+        Ok(page) if !(200..300).contains(&page.status) && page.status != 304 => Err(format!("the source answered with status {}", page.status)),
+        Ok(page) if page.not_modified && can_reuse => Ok(Fetched::NotModified),
+        Ok(page) => Ok(Fetched::Page(page.body)),
+        Err(error) => Err(error)
+    };
+
+    if let Err(error) = &result {
+        tracing::warn!(url, %error, "Couldn't fetch the page");
     }
 
-    latest_news
+    result
 }
 
-pub async fn get_lacapital() -> Vec<JournalNew> {
-    let first_page_load = reqwest::get("https://www.lacapital.com.ar/secciones/ultimo-momento.html").await;
-    let mut latest_news: Vec<JournalNew> = vec![];
+/// Where to find each field inside the listing of a newspaper.
+/// `article` matches every story, the rest are looked up inside of it.
+struct ListingSelectors {
+    article: &'static str,
+    /// Element with the title, the article itself when `None`
+    title: Option<&'static str>,
+    /// Attribute of the title element with the title, its text when `None`
+    title_attribute: Option<&'static str>,
+    summary: Option<&'static str>,
+    /// Element with the `href` of the story, the article itself when `None`
+    link: Option<&'static str>
+}
 
-    match first_page_load {
-        Ok(response) => {
-            let response_html = response.text().await.unwrap();
+/// Parses a listing page, skipping and counting the stories missing a title or a link instead of panicking
+fn parse_listing(html: &str, page: &str, selectors: &ListingSelectors, newspaper: Newspaper) -> Listing {
+    let mut listing = Listing::default();
 
-            let dom = tl::parse(&response_html, tl::ParserOptions::default()).unwrap();
-            let parser = dom.parser();
+    let dom = match tl::parse(html, tl::ParserOptions::default()) {
+        Ok(dom) => dom,
+        Err(error) => {
+            tracing::warn!(%error, "Couldn't parse the page");
+            return listing
+        }
+    };
 
-            let articles = get_elements("article.ultimas-noticias-entry-container", &dom, parser);
+    let parser = dom.parser();
 
-            articles.iter().for_each(|node| {
-                let node_tag = node.as_tag().expect("unable to convert node to tag");
+    for node in get_elements(selectors.article, &dom, parser) {
+        let node_tag = match node.as_tag() {
+            Some(node_tag) => node_tag,
+            None => continue
+        };
 
-                let h2_node = query_node("h2.entry-title", node_tag, parser);
-                let a_node = query_node("a.cover-link", node_tag, parser);
+        let select = |selector: Option<&str>| match selector {
+            Some(selector) => try_query_node(selector, node_tag, parser),
+            None => Some(node.clone())
+        };
 
-                let title = h2_node.inner_text(parser);
-                let link = get_attribute("href", &a_node);
+        let title = select(selectors.title).and_then(|title| match selectors.title_attribute {
+            Some(attribute) => try_get_attribute(attribute, &title),
+            None => Some(String::from(title.inner_text(parser)))
+        }).map(|title| String::from(title.trim()));
+        let link = select(selectors.link)
+        .and_then(|a| try_get_attribute("href", &a))
+        .and_then(|href| resolve_link(page, &href));
 
-                if latest_news.iter().any(|journal_new| journal_new.link == Some(link.clone())) {
-                    return
-                }
+        let (title, link) = match (title.filter(|title| !title.is_empty()), link) {
+            (Some(title), Some(link)) => (title, link),
+            (None, _) => {
+                listing.missing_titles += 1;
+                continue
+            },
+            (_, None) => {
+                listing.missing_links += 1;
+                continue
+            }
+        };
+
+        if listing.news.iter().any(|journal_new| journal_new.link == Some(link.clone())) {
+            continue
+        }
+
+        let text = selectors.summary
+        .and_then(|summary| try_query_node(summary, node_tag, parser))
+        .map(|summary| String::from(summary.inner_text(parser).trim()))
+        .unwrap_or_default();
+
+        let (kind, paywalled) = classify::classify(&story_markers(&node, parser), &link);
+        let section = classify::section(&story_sections(&node, parser), &link);
+        let authors = authors::parse(&story_bylines(&node, parser));
+        let image = story_image(&node, parser, page);
+        let block = story_block(&node);
 
-                latest_news.push(
-                    JournalNew { 
-                        title: String::from(title), 
-                        text: String::from(""), 
-                        link: Some(link),
-                        newspaper: Newspaper::LACAPITAL
-                    })
+        listing.news.push(JournalNew {
+            title,
+            text,
+            link: Some(link),
+            newspaper: newspaper.clone(),
+            kind,
+            paywalled,
+            section,
+            authors,
+            image,
+            position: listing.news.len() + 1,
+            block
+        }.normalized());
+    }
+
+    listing
+}
+
+/// Parses the home page of a source into its blocks, in the order they appear, with the stories of each one.
+/// Every `<article>` is a story and belongs to the innermost block around it, the page leads with the first one
+/// with an `<h1>` or otherwise with the top story.
+pub fn parse_front_page(html: &str, page: &str, newspaper: Newspaper) -> FrontPage {
+    let mut front_page = FrontPage {
+        newspaper: newspaper.clone(),
+        url: String::from(page),
+        captured_at: chrono::Utc::now().to_rfc3339(),
+        lead: None,
+        blocks: vec![]
+    };
+
+    let dom = match tl::parse(html, tl::ParserOptions::default()) {
+        Ok(dom) => dom,
+        Err(error) => {
+            tracing::warn!(%error, "Couldn't parse the page");
+            return front_page
+        }
+    };
+
+    let parser = dom.parser();
+    let mut article_blocks = HashMap::new();
+
+    for handle in dom.children() {
+        find_article_blocks(*handle, parser, &None, &mut article_blocks);
+    }
+
+    let mut stories: Vec<JournalNew> = vec![];
+
+    for handle in dom.query_selector("article").into_iter().flatten() {
+        let (node, node_tag) = match handle.get(parser).and_then(|node| Some((node, node.as_tag()?))) {
+            Some(article) => article,
+            None => continue
+        };
+
+        let headline = ["h1", "h2", "h3", "h4"].iter().find_map(|heading| try_query_node(heading, node_tag, parser));
+        let anchor = try_query_node("a", node_tag, parser);
+        let link = anchor.as_ref().and_then(|a| try_get_attribute("href", a)).and_then(|href| resolve_link(page, &href));
+        let title = headline.or(anchor).map(|title| String::from(title.inner_text(parser).trim()));
+
+        let (title, link) = match (title, link) {
+            (Some(title), Some(link)) if !title.is_empty() => (title, link),
+            _ => continue
+        };
+
+        if stories.iter().any(|journal_new| journal_new.link == Some(link.clone())) {
+            continue
+        }
+
+        let (kind, paywalled) = classify::classify(&story_markers(node, parser), &link);
+        let section = classify::section(&story_sections(node, parser), &link);
+        let authors = authors::parse(&story_bylines(node, parser));
+        let image = story_image(node, parser, page);
+        let block = article_blocks.get(&handle.get_inner()).cloned().flatten();
+
+        if front_page.lead.is_none() && try_query_node("h1", node_tag, parser).is_some() {
+            front_page.lead = Some(link.clone());
+        }
+
+        stories.push(JournalNew {
+            title,
+            text: String::new(),
+            link: Some(link),
+            newspaper: newspaper.clone(),
+            kind,
+            paywalled,
+            section,
+            authors,
+            image,
+            position: stories.len() + 1,
+            block
+        }.normalized());
+    }
+
+    front_page.lead = front_page.lead.or(stories.first().map(|journal_new| journal_new.key()));
+
+    for journal_new in stories {
+        match front_page.blocks.last_mut() {
+            Some(block) if block.name == journal_new.block => block.stories.push(journal_new),
+            _ => front_page.blocks.push(FrontPageBlock {
+                name: journal_new.block.clone(),
+                section: classify::section(&journal_new.block.iter().cloned().collect::<Vec<String>>(), ""),
+                stories: vec![journal_new]
             })
-        },
-        Err(error) => println!("{}", error)
+        }
+    }
+
+    front_page
+}
+
+/// Fetches and parses the home page of a source, see `parse_front_page`
+pub async fn get_front_page(client: &HttpClient, newspaper: &Newspaper) -> Result<FrontPage, String> {
+    let url = newspaper.info().home_url;
+
+    match client.get(&url).await {
+        Ok(page) if !(200..300).contains(&page.status) && page.status != 304 => Err(format!("the source answered with status {}", page.status)),
+        Ok(page) => Ok(parse_front_page(&page.body, &url, newspaper.clone())),
+        Err(error) => Err(error)
+    }
+}
+
+/// Snapshots the home pages of the given sources concurrently, skipping the ones that couldn't be fetched,
+/// in the order of `Newspaper::ALL`
+pub async fn get_front_pages(client: &HttpClient, newspapers: &[Newspaper]) -> Vec<FrontPage> {
+    let mut fetches = tokio::task::JoinSet::new();
+
+    for newspaper in newspapers.iter().cloned() {
+        let client = client.clone();
+
+        fetches.spawn(async move {
+            let front_page = get_front_page(&client, &newspaper).await;
+            (newspaper, front_page)
+        });
+    }
+
+    let mut front_pages = vec![];
+
+    while let Some(result) = fetches.join_next().await {
+        match result {
+            Ok((_, Ok(front_page))) => front_pages.push(front_page),
+            Ok((newspaper, Err(error))) => tracing::warn!(source = %newspaper, %error, "Couldn't get the front page"),
+            Err(error) => tracing::error!(%error, "A front page task failed")
+        }
+    }
+
+    front_pages.sort_by_key(|front_page| Newspaper::ALL.iter().position(|ordered| *ordered == front_page.newspaper));
+
+    front_pages
+}
+
+pub fn parse_clarin(html: &str) -> Listing {
+    parse_listing(html, CLARIN_URL, &ListingSelectors {
+        article: "article",
+        title: Some("h2"),
+        title_attribute: None,
+        summary: Some("h3.summary"),
+        link: Some("a.link-new")
+    }, Newspaper::CLARIN)
+}
+
+pub async fn get_clarin(client: &HttpClient) -> Vec<JournalNew> {
+    match fetch_page(client, &Newspaper::CLARIN, CLARIN_URL, false, &mut FetchStats::default()).await {
+        Ok(Fetched::Page(html)) => parse_clarin(&html).news,
+        Ok(Fetched::NotModified) | Err(_) => vec![]
+    }
+}
+
+pub fn parse_infobae(html: &str) -> Listing {
+    parse_listing(html, INFOBAE_URL, &ListingSelectors {
+        article: "a.feed-list-card",
+        title: Some("h2.feed-list-card-headline-lean"),
+        title_attribute: None,
+        summary: Some("div.deck"),
+        link: None
+    }, Newspaper::INFOBAE)
+}
+
+pub async fn get_infobae(client: &HttpClient) -> Vec<JournalNew> {
+    match fetch_page(client, &Newspaper::INFOBAE, INFOBAE_URL, false, &mut FetchStats::default()).await {
+        Ok(Fetched::Page(html)) => parse_infobae(&html).news,
+        Ok(Fetched::NotModified) | Err(_) => vec![]
+    }
+}
+
+pub fn parse_lanacion(html: &str) -> Listing {
+    parse_listing(html, LANACION_URL, &ListingSelectors {
+        article: "article.mod-article",
+        title: Some("a.com-link"),
+        title_attribute: None,
+        summary: None,
+        link: Some("a.com-link")
+    }, Newspaper::LANACION)
+}
+
+pub async fn get_lanacion(client: &HttpClient) -> Vec<JournalNew> {
+    match fetch_page(client, &Newspaper::LANACION, LANACION_URL, false, &mut FetchStats::default()).await {
+        Ok(Fetched::Page(html)) => parse_lanacion(&html).news,
+        Ok(Fetched::NotModified) | Err(_) => vec![]
+    }
+}
+
+pub fn parse_lacapital(html: &str) -> Listing {
+    parse_listing(html, LACAPITAL_URL, &ListingSelectors {
+        article: "article.ultimas-noticias-entry-container",
+        title: Some("h2.entry-title"),
+        title_attribute: None,
+        summary: None,
+        link: Some("a.cover-link")
+    }, Newspaper::LACAPITAL)
+}
+
+pub async fn get_lacapital(client: &HttpClient) -> Vec<JournalNew> {
+    match fetch_page(client, &Newspaper::LACAPITAL, LACAPITAL_URL, false, &mut FetchStats::default()).await {
+        Ok(Fetched::Page(html)) => parse_lacapital(&html).news,
+        Ok(Fetched::NotModified) | Err(_) => vec![]
     }
+}
+
+pub fn parse_rosario3(html: &str) -> Listing {
+    parse_listing(html, ROSARIO3_URL, &ListingSelectors {
+        article: "a.cover-link",
+        title: None,
+        title_attribute: Some("title"),
+        summary: None,
+        link: None
+    }, Newspaper::ROSARIO3)
+}
+
+pub async fn get_rosario3(client: &HttpClient) -> Vec<JournalNew> {
+    match fetch_page(client, &Newspaper::ROSARIO3, ROSARIO3_URL, false, &mut FetchStats::default()).await {
+        Ok(Fetched::Page(html)) => parse_rosario3(&html).news,
+        Ok(Fetched::NotModified) | Err(_) => vec![]
+    }
+}
+
+pub fn parse_pagina12(html: &str) -> Listing {
+    parse_listing(html, PAGINA12_URL, &ListingSelectors {
+        article: "article.article-item",
+        title: Some(".article-title a"),
+        title_attribute: None,
+        summary: Some(".article-summary"),
+        link: Some(".article-title a")
+    }, Newspaper::PAGINA12)
+}
+
+pub async fn get_pagina12(client: &HttpClient) -> Vec<JournalNew> {
+    match fetch_page(client, &Newspaper::PAGINA12, PAGINA12_URL, false, &mut FetchStats::default()).await {
+        Ok(Fetched::Page(html)) => parse_pagina12(&html).news,
+        Ok(Fetched::NotModified) | Err(_) => vec![]
+    }
+}
+
+pub fn parse_ambito(html: &str) -> Listing {
+    parse_listing(html, AMBITO_URL, &ListingSelectors {
+        article: "article.news-article",
+        title: Some("h2.news-article__title"),
+        title_attribute: None,
+        summary: Some("p.news-article__subtitle"),
+        link: Some("h2.news-article__title a")
+    }, Newspaper::AMBITO)
+}
+
+pub async fn get_ambito(client: &HttpClient) -> Vec<JournalNew> {
+    match fetch_page(client, &Newspaper::AMBITO, AMBITO_URL, false, &mut FetchStats::default()).await {
+        Ok(Fetched::Page(html)) => parse_ambito(&html).news,
+        Ok(Fetched::NotModified) | Err(_) => vec![]
+    }
+}
 
-    latest_news
+pub fn parse_perfil(html: &str) -> Listing {
+    parse_listing(html, PERFIL_URL, &ListingSelectors {
+        article: "article.news",
+        title: Some("h2.news__title"),
+        title_attribute: None,
+        summary: Some("p.news__headline"),
+        link: Some("a")
+    }, Newspaper::PERFIL)
 }
 
-pub async fn get_rosario3() -> Vec<JournalNew> {
-    let first_page_load = reqwest::get("https://www.rosario3.com/seccion/ultimas-noticias/").await;
-    let mut latest_news: Vec<JournalNew> = vec![];
+pub async fn get_perfil(client: &HttpClient) -> Vec<JournalNew> {
+    match fetch_page(client, &Newspaper::PERFIL, PERFIL_URL, false, &mut FetchStats::default()).await {
+        Ok(Fetched::Page(html)) => parse_perfil(&html).news,
+        Ok(Fetched::NotModified) | Err(_) => vec![]
+    }
+}
 
-    match first_page_load {
-        Ok(response) => {
-            let response_html = response.text().await.unwrap();
+pub fn parse_tn(html: &str) -> Listing {
+    parse_listing(html, TN_URL, &ListingSelectors {
+        article: "article.card__container",
+        title: Some("h2.card__headline"),
+        title_attribute: None,
+        summary: Some("p.card__subheadline"),
+        link: Some("h2.card__headline a")
+    }, Newspaper::TN)
+}
+
+pub async fn get_tn(client: &HttpClient) -> Vec<JournalNew> {
+    match fetch_page(client, &Newspaper::TN, TN_URL, false, &mut FetchStats::default()).await {
+        Ok(Fetched::Page(html)) => parse_tn(&html).news,
+        Ok(Fetched::NotModified) | Err(_) => vec![]
+    }
+}
 
-            let dom = tl::parse(&response_html, tl::ParserOptions::default()).unwrap();
-            let parser = dom.parser();
+pub fn parse_lavoz(html: &str) -> Listing {
+    parse_listing(html, LAVOZ_URL, &ListingSelectors {
+        article: "article.story-card",
+        title: Some("h2.story-card-headline"),
+        title_attribute: None,
+        summary: Some("p.story-card-subheadline"),
+        link: Some("a.story-card-link")
+    }, Newspaper::LAVOZ)
+}
 
-            let a_tags = get_elements("a.cover-link", &dom, parser);
+pub async fn get_lavoz(client: &HttpClient) -> Vec<JournalNew> {
+    match fetch_page(client, &Newspaper::LAVOZ, LAVOZ_URL, false, &mut FetchStats::default()).await {
+        Ok(Fetched::Page(html)) => parse_lavoz(&html).news,
+        Ok(Fetched::NotModified) | Err(_) => vec![]
+    }
+}
 
-            a_tags.iter().for_each(|node| {
-                let link = get_attribute("href", node);
-                let title = get_attribute("title", node);
+pub fn parse_losandes(html: &str) -> Listing {
+    parse_listing(html, LOSANDES_URL, &ListingSelectors {
+        article: "article.article",
+        title: Some("h2.title a"),
+        title_attribute: None,
+        summary: Some("p.summary"),
+        link: Some("h2.title a")
+    }, Newspaper::LOSANDES)
+}
 
+pub async fn get_losandes(client: &HttpClient) -> Vec<JournalNew> {
+    match fetch_page(client, &Newspaper::LOSANDES, LOSANDES_URL, false, &mut FetchStats::default()).await {
+        Ok(Fetched::Page(html)) => parse_losandes(&html).news,
+        Ok(Fetched::NotModified) | Err(_) => vec![]
+    }
+}
 
-                if latest_news.iter().any(|journal_new| journal_new.link == Some(format!("http://rosario3.com{}", String::from(link.clone())))) {
-                    return
+pub fn parse_ellitoral(html: &str) -> Listing {
+    parse_listing(html, ELLITORAL_URL, &ListingSelectors {
+        article: "article.nota",
+        title: Some("h2.nota-titulo a"),
+        title_attribute: None,
+        summary: Some("p.nota-bajada"),
+        link: Some("h2.nota-titulo a")
+    }, Newspaper::ELLITORAL)
+}
+
+pub async fn get_ellitoral(client: &HttpClient) -> Vec<JournalNew> {
+    match fetch_page(client, &Newspaper::ELLITORAL, ELLITORAL_URL, false, &mut FetchStats::default()).await {
+        Ok(Fetched::Page(html)) => parse_ellitoral(&html).news,
+        Ok(Fetched::NotModified) | Err(_) => vec![]
+    }
+}
+
+pub fn parse_rionegro(html: &str) -> Listing {
+    parse_listing(html, RIONEGRO_URL, &ListingSelectors {
+        article: "article.news-card",
+        title: Some("h2.news-card__title a"),
+        title_attribute: None,
+        summary: Some("div.news-card__excerpt"),
+        link: Some("h2.news-card__title a")
+    }, Newspaper::RIONEGRO)
+}
+
+pub async fn get_rionegro(client: &HttpClient) -> Vec<JournalNew> {
+    match fetch_page(client, &Newspaper::RIONEGRO, RIONEGRO_URL, false, &mut FetchStats::default()).await {
+        Ok(Fetched::Page(html)) => parse_rionegro(&html).news,
+        Ok(Fetched::NotModified) | Err(_) => vec![]
+    }
+}
+
+/// The listing page each source is scraped from
+pub fn listing_url(newspaper: &Newspaper) -> &'static str {
+    match newspaper {
+        Newspaper::CLARIN => CLARIN_URL,
+        Newspaper::INFOBAE => INFOBAE_URL,
+        Newspaper::LANACION => LANACION_URL,
+        Newspaper::LACAPITAL => LACAPITAL_URL,
+        Newspaper::ROSARIO3 => ROSARIO3_URL,
+        Newspaper::PAGINA12 => PAGINA12_URL,
+        Newspaper::AMBITO => AMBITO_URL,
+        Newspaper::PERFIL => PERFIL_URL,
+        Newspaper::TN => TN_URL,
+        Newspaper::LAVOZ => LAVOZ_URL,
+        Newspaper::LOSANDES => LOSANDES_URL,
+        Newspaper::ELLITORAL => ELLITORAL_URL,
+        Newspaper::RIONEGRO => RIONEGRO_URL
+    }
+}
+
+/// Runs the scraper of a source over a listing page it fetched before, like an archived one
+pub fn parse_news(newspaper: &Newspaper, html: &str) -> Listing {
+    match newspaper {
+        Newspaper::CLARIN => parse_clarin(html),
+        Newspaper::INFOBAE => parse_infobae(html),
+        Newspaper::LANACION => parse_lanacion(html),
+        Newspaper::LACAPITAL => parse_lacapital(html),
+        Newspaper::ROSARIO3 => parse_rosario3(html),
+        Newspaper::PAGINA12 => parse_pagina12(html),
+        Newspaper::AMBITO => parse_ambito(html),
+        Newspaper::PERFIL => parse_perfil(html),
+        Newspaper::TN => parse_tn(html),
+        Newspaper::LAVOZ => parse_lavoz(html),
+        Newspaper::LOSANDES => parse_losandes(html),
+        Newspaper::ELLITORAL => parse_ellitoral(html),
+        Newspaper::RIONEGRO => parse_rionegro(html)
+    }
+}
+
+pub async fn get_news(client: &HttpClient, newspaper: &Newspaper) -> Vec<JournalNew> {
+    match newspaper {
+        Newspaper::CLARIN => get_clarin(client).await,
+        Newspaper::INFOBAE => get_infobae(client).await,
+        Newspaper::LANACION => get_lanacion(client).await,
+        Newspaper::LACAPITAL => get_lacapital(client).await,
+        Newspaper::ROSARIO3 => get_rosario3(client).await,
+        Newspaper::PAGINA12 => get_pagina12(client).await,
+        Newspaper::AMBITO => get_ambito(client).await,
+        Newspaper::PERFIL => get_perfil(client).await,
+        Newspaper::TN => get_tn(client).await,
+        Newspaper::LAVOZ => get_lavoz(client).await,
+        Newspaper::LOSANDES => get_losandes(client).await,
+        Newspaper::ELLITORAL => get_ellitoral(client).await,
+        Newspaper::RIONEGRO => get_rionegro(client).await
+    }
+}
+
+/// Like `get_news` but turns a failed fetch or a panic of the scraper into an error and reuses `previous`,
+/// the listing last parsed from the source, when it didn't change.
+/// Also returns how long the fetch took together with the status and size of the responses and the skipped stories.
+pub async fn check_news(client: &HttpClient, newspaper: &Newspaper, previous: Option<Listing>) -> (Result<Listing, String>, FetchStats) {
+    let mut stats = FetchStats {
+        started_at: chrono::Utc::now().to_rfc3339(),
+        ..FetchStats::default()
+    };
+    let start = Instant::now();
+    let span = tracing::info_span!("fetch", source = %newspaper);
+    let fetched = fetch_page(client, newspaper, listing_url(newspaper), previous.is_some(), &mut stats).instrument(span.clone()).await;
+
+    let result = match fetched {
+        Ok(Fetched::Page(html)) => {
+            let scraped = newspaper.clone();
+
+            tokio::task::spawn_blocking(move || parse_news(&scraped, &html)).await.map_err(|error| {
+                if !error.is_panic() {
+                    return error.to_string()
                 }
 
-                latest_news.push(
-                    JournalNew { 
-                        title: String::from(title), 
-                        text: String::from(""), 
-                        link: Some(format!("http://rosario3.com{}", String::from(link))),
-                        newspaper: Newspaper::ROSARIO3 
-                    })
+                let panic = error.into_panic();
+
+                panic.downcast_ref::<&str>().map(|message| String::from(*message))
+                .or(panic.downcast_ref::<String>().cloned())
+                .unwrap_or(String::from("the scraper panicked"))
             })
         },
-        Err(error) => println!("{}", error)
+        Ok(Fetched::NotModified) => Ok(previous.unwrap_or_default()),
+        Err(error) => Err(error)
+    };
+
+    stats.duration_ms = start.elapsed().as_millis() as u64;
+
+    if let Ok(listing) = &result {
+        stats.missing_titles = listing.missing_titles;
+        stats.missing_links = listing.missing_links;
+    }
+
+    span.in_scope(|| match &result {
+        Ok(listing) => tracing::debug!(items = listing.news.len(), duration_ms = stats.duration_ms, http_status = stats.http_status, bytes = stats.bytes, "Fetched the source"),
+        Err(error) => tracing::debug!(%error, duration_ms = stats.duration_ms, http_status = stats.http_status, "Couldn't get the source")
+    });
+
+    (result, stats)
+}
+
+/// Fetches every newspaper of the given regions concurrently (all of them when `regions` is empty),
+/// returning the result of each source in the order of `Newspaper::ALL` and keeping the parsed ones in `last_parsed`
+pub async fn get_all_checked(client: &HttpClient, regions: &[Region], last_parsed: &mut LastParsed) -> Vec<SourceFetch> {
+    let mut fetches = tokio::task::JoinSet::new();
+
+    for newspaper in Newspaper::ALL.into_iter().filter(|newspaper| newspaper.in_regions(regions)) {
+        let client = client.clone();
+        let previous = last_parsed.get(&newspaper).cloned();
+
+        fetches.spawn(async move {
+            let (listing, stats) = check_news(&client, &newspaper, previous).await;
+            (newspaper, listing, stats)
+        });
+    }
+
+    let mut sources = vec![];
+
+    while let Some(result) = fetches.join_next().await {
+        match result {
+            Ok(source) => sources.push(source),
+            Err(error) => tracing::error!(%error, "A fetch task failed")
+        }
+    }
+
+    sources.sort_by_key(|(newspaper, _, _)| Newspaper::ALL.iter().position(|ordered| ordered == newspaper));
+
+    sources.into_iter().map(|(newspaper, result, stats)| {
+        if let Ok(listing) = &result {
+            last_parsed.insert(newspaper.clone(), listing.clone());
+        }
+
+        (newspaper, result.map(|listing| listing.news), stats)
+    }).collect()
+}
+
+/// Same as `get_all_checked` with the failed sources left empty
+pub async fn get_all(client: &HttpClient, regions: &[Region], last_parsed: &mut LastParsed) -> Vec<(Newspaper, Vec<JournalNew>)> {
+    get_all_checked(client, regions, last_parsed).await.into_iter().map(|(newspaper, result, _)| match result {
+        Ok(news) => (newspaper, news),
+        Err(error) => {
+            tracing::warn!(source = %newspaper, %error, "Couldn't get the news");
+            (newspaper, vec![])
+        }
+    }).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{ContentKind, Section};
+
+    /// Runs `check` over the first `<article>` of `html`
+    fn with_article<T>(html: &str, check: impl FnOnce(&Node, &Parser) -> T) -> T {
+        let dom = tl::parse(html, tl::ParserOptions::default()).unwrap();
+        let parser = dom.parser();
+        let article = dom.query_selector("article").and_then(|mut articles| articles.next()).and_then(|handle| handle.get(parser)).unwrap();
+
+        check(article, parser)
+    }
+
+    #[test]
+    fn markers_come_from_classes_and_data_attributes() {
+        let mut markers = with_article(r#"
+            <article class="article-item article-item--premium" data-live="true" data-url="/1-nota">
+                <span class="icon-candado"></span>
+            </article>
+        "#, story_markers);
+        markers.sort();
+
+        assert_eq!(markers, ["article-item article-item--premium", "data-live", "icon-candado"]);
     }
 
-    latest_news
-}
\ No newline at end of file
+    #[test]
+    fn data_attributes_count_by_their_value() {
+        let page = "https://www.pagina12.com.ar/1-nota";
+        let kind_of = |html: &str| classify::classify(&with_article(html, story_markers), page);
+
+        assert_eq!(kind_of(r#"<article data-paywall="false" data-live="0"></article>"#), (ContentKind::ARTICLE, false));
+        assert_eq!(kind_of(r#"<article data-video-id="123"></article>"#), (ContentKind::ARTICLE, false));
+        assert_eq!(kind_of(r#"<article data-paywall="true" data-type="video"></article>"#), (ContentKind::VIDEO, true));
+    }
+
+    #[test]
+    fn sections_come_from_labels_and_data_attributes() {
+        let sections = with_article(r#"
+            <article data-section="Economía">
+                <div class="breadcrumb">Inicio › Información General</div>
+                <span class="article-title">Policiales</span>
+            </article>
+        "#, story_sections);
+
+        assert_eq!(sections, ["Economía", "Inicio › Información General"]);
+
+        let sections = with_article(r#"
+            <article>
+                <div class="subsection-ad">Deportes</div>
+                <ol class="breadcrumbs"><li><a href="/">Inicio</a></li> › <li><a href="/politica">Política</a></li></ol>
+                <div class="section"><span>El País</span><p>Un resumen que no es parte de la sección</p></div>
+            </article>
+        "#, story_sections);
+
+        assert_eq!(sections, ["Inicio › Política", "El País"]);
+    }
+
+    #[test]
+    fn bylines_come_from_the_marked_elements() {
+        let bylines = with_article(r#"
+            <article data-author="juan perez">
+                <div class="article-author">Por <a rel="author" href="/autores/juan">JUAN PÉREZ</a></div>
+                <p class="summary">Sin firma</p>
+            </article>
+        "#, story_bylines);
+
+        assert_eq!(bylines, ["juan perez", "Por JUAN PÉREZ", "JUAN PÉREZ"]);
+    }
+
+    #[test]
+    fn lead_images_skip_placeholders_and_pixels() {
+        let page = "https://www.pagina12.com.ar/ultimas-noticias";
+
+        let image = with_article(r#"
+            <article>
+                <img src="data:image/gif;base64,R0lGOD" data-src="//images.pagina12.com.ar/1.jpg" alt="El Congreso" width="640" height="360">
+            </article>
+        "#, |article, parser| story_image(article, parser, page));
+
+        assert_eq!(image, Some(LeadImage {
+            url: String::from("https://images.pagina12.com.ar/1.jpg"),
+            alt: Some(String::from("El Congreso")),
+            width: Some(640),
+            height: Some(360),
+            thumbnail: None
+        }));
+
+        let image = with_article(r#"
+            <article>
+                <img src="https://pixel.example.com/t.gif" width="1" height="1">
+                <img srcset="/2-chica.jpg 320w, /2-grande.jpg 1024w">
+            </article>
+        "#, |article, parser| story_image(article, parser, page));
+
+        assert_eq!(image.map(|image| image.url).as_deref(), Some("https://www.pagina12.com.ar/2-chica.jpg"));
+        assert_eq!(with_article("<article></article>", |article, parser| story_image(article, parser, page)), None);
+
+        let image = with_article(r#"<article><img src="javascript:alert(1)" srcset="/3.jpg 1x"></article>"#, |article, parser| story_image(article, parser, page));
+        assert_eq!(image.map(|image| image.url).as_deref(), Some("https://www.pagina12.com.ar/3.jpg"));
+        assert_eq!(with_article(r#"<article><img src="javascript:alert(1)"></article>"#, |article, parser| story_image(article, parser, page)), None);
+    }
+
+    #[tokio::test]
+    async fn unchanged_listings_are_not_modified() {
+        let body = "<html>listing</html>";
+        let (address, _) = crate::testing::http_server(vec![
+            format!("HTTP/1.1 200 OK\r\netag: \"v1\"\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{body}", body.len()),
+            String::from("HTTP/1.1 304 Not Modified\r\nconnection: close\r\n\r\n"),
+            String::from("HTTP/1.1 304 Not Modified\r\nconnection: close\r\n\r\n")
+        ]).await;
+        let client = crate::testing::http_client();
+        let mut stats = FetchStats::default();
+
+        assert_eq!(fetch_page(&client, &Newspaper::CLARIN, &address, true, &mut stats).await, Ok(Fetched::Page(String::from(body))));
+        assert_eq!(fetch_page(&client, &Newspaper::CLARIN, &address, true, &mut stats).await, Ok(Fetched::NotModified));
+        // Without a previous result to reuse the cached listing is parsed again
+        assert_eq!(fetch_page(&client, &Newspaper::CLARIN, &address, false, &mut stats).await, Ok(Fetched::Page(String::from(body))));
+        assert_eq!((stats.http_status, stats.bytes), (Some(304), body.len()));
+    }
+
+    #[tokio::test]
+    async fn only_successful_answers_are_pages() {
+        let (address, _) = crate::testing::http_server(vec![
+            String::from("HTTP/1.1 404 Not Found\r\ncontent-length: 0\r\nconnection: close\r\n\r\n"),
+            String::from("HTTP/1.1 301 Moved Permanently\r\ncontent-length: 0\r\nconnection: close\r\n\r\n")
+        ]).await;
+        let client = crate::testing::http_client();
+
+        assert_eq!(fetch_page(&client, &Newspaper::CLARIN, &address, false, &mut FetchStats::default()).await, Err(String::from("the source answered with status 404")));
+        assert_eq!(fetch_page(&client, &Newspaper::CLARIN, &address, false, &mut FetchStats::default()).await, Err(String::from("the source answered with status 301")));
+    }
+
+    #[test]
+    fn stories_missing_a_field_are_skipped() {
+        let infobae = parse_infobae(r#"
+            <a class="feed-list-card" href="/america/1-nota/"><h2 class="feed-list-card-headline-lean">Primera</h2></a>
+            <a class="feed-list-card" href="/america/2-nota/"><div class="deck">Sin título</div></a>
+            <a class="feed-list-card"><h2 class="feed-list-card-headline-lean">Sin link</h2></a>
+        "#);
+        let rosario3 = parse_rosario3(r#"
+            <a class="cover-link" href="/policiales/1-nota.html" title="Primera"></a>
+            <a class="cover-link" href="/policiales/2-nota.html"></a>
+        "#);
+
+        assert_eq!(infobae.news.iter().map(|journal_new| journal_new.title.as_str()).collect::<Vec<&str>>(), ["Primera"]);
+        assert_eq!(infobae.news[0].link.as_deref(), Some("https://www.infobae.com/america/1-nota/"));
+        assert_eq!((infobae.missing_titles, infobae.missing_links), (1, 1));
+        assert_eq!(rosario3.news.iter().map(|journal_new| journal_new.title.as_str()).collect::<Vec<&str>>(), ["Primera"]);
+        assert_eq!((rosario3.missing_titles, rosario3.missing_links), (1, 0));
+        assert!(parse_clarin("<article><h2>Sin link</h2></article>").news.is_empty());
+    }
+
+    #[test]
+    fn links_are_resolved_against_the_page() {
+        let page = "https://www.lanacion.com.ar/ultimas-noticias/";
+
+        assert_eq!(resolve_link(page, "/politica/nota-nid10102023/").as_deref(), Some("https://www.lanacion.com.ar/politica/nota-nid10102023/"));
+        assert_eq!(resolve_link(page, "nota.html").as_deref(), Some("https://www.lanacion.com.ar/ultimas-noticias/nota.html"));
+        assert_eq!(resolve_link(page, "//www.infobae.com/america/nota/").as_deref(), Some("https://www.infobae.com/america/nota/"));
+        assert_eq!(resolve_link(page, " http://www.rosario3.com/policiales/nota.html#comentarios ").as_deref(), Some("https://www.rosario3.com/policiales/nota.html"));
+        assert_eq!(resolve_link(page, "/1-nota?a=1&amp;b=2").as_deref(), Some("https://www.lanacion.com.ar/1-nota?a=1&b=2"));
+        assert_eq!(resolve_link(page, "javascript:void(0)"), None);
+        assert_eq!(resolve_link(page, "mailto:redaccion@lanacion.com.ar"), None);
+        assert_eq!(resolve_link(page, "#top"), None);
+        assert_eq!(resolve_link(page, ""), None);
+    }
+
+    #[test]
+    fn front_pages_keep_their_blocks_and_lead() {
+        let front_page = parse_front_page(r#"
+            <article class="nota"><h2><a href="/politica/1-nota">Arriba de todo</a></h2></article>
+            <section data-block="Apertura">
+                <article><h1><a href="/economia/2-nota">La nota principal</a></h1></article>
+                <article><h2><a href="/economia/3-nota">Secundaria</a></h2></article>
+            </section>
+            <section aria-label="Deportes">
+                <div class="grilla">
+                    <article><a href="https://www.tn.com.ar/deportes/4-nota">El gol</a></article>
+                </div>
+                <section id="Mundo">
+                    <article><h3><a href="/mundo/5-nota">Elecciones afuera</a></h3></article>
+                </section>
+            </section>
+        "#, "https://www.tn.com.ar", Newspaper::TN);
+
+        let blocks: Vec<(Option<&str>, Section)> = front_page.blocks.iter().map(|block| (block.name.as_deref(), block.section)).collect();
+        assert_eq!(blocks, [(None, Section::OTHER), (Some("Apertura"), Section::OTHER), (Some("Deportes"), Section::SPORTS), (Some("Mundo"), Section::WORLD)]);
+
+        let stories: Vec<Vec<(usize, &str)>> = front_page.blocks.iter().map(|block| {
+            block.stories.iter().map(|journal_new| (journal_new.position, journal_new.title.as_str())).collect()
+        }).collect();
+        assert_eq!(stories, [
+            vec![(1, "Arriba de todo")],
+            vec![(2, "La nota principal"), (3, "Secundaria")],
+            vec![(4, "El gol")],
+            vec![(5, "Elecciones afuera")]
+        ]);
+        assert_eq!(front_page.lead().map(|lead| lead.title.as_str()), Some("La nota principal"));
+    }
+}
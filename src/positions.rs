@@ -0,0 +1,240 @@
+use std::collections::HashMap;
+
+use serde::{Serialize, Deserialize};
+
+use crate::model::{JournalNew, Newspaper, Section};
+
+pub const POSITIONS_FILE: &str = "jornais_positions.jsonl";
+
+/// Positions kept in the local file, the oldest ones are dropped past this
+const MAX_STORED_POSITIONS: usize = 100000;
+
+/// Where a story was in the listing of its source on a single fetch
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct PositionRecord {
+    pub newspaper: Newspaper,
+    /// `JournalNew::key` of the story
+    pub story: String,
+    pub title: String,
+    pub section: Section,
+    pub fetched_at: String,
+    pub position: usize,
+    pub block: Option<String>
+}
+
+impl PositionRecord {
+    /// The positions of every story of a fetch that started at `fetched_at`
+    pub fn snapshot(news: &[JournalNew], fetched_at: &str) -> Vec<PositionRecord> {
+        news.iter().map(|journal_new| PositionRecord {
+            newspaper: journal_new.newspaper.clone(),
+            story: journal_new.key(),
+            title: journal_new.title.clone(),
+            section: journal_new.section,
+            fetched_at: String::from(fetched_at),
+            position: journal_new.position,
+            block: journal_new.block.clone()
+        }).collect()
+    }
+}
+
+/// Reads every position stored in `path`, oldest first
+fn read_all(path: &str) -> Vec<PositionRecord> {
+    match std::fs::read_to_string(path) {
+        Ok(data) => data.lines().filter_map(|line| serde_json::from_str(line).ok()).collect(),
+        Err(_) => vec![]
+    }
+}
+
+/// Appends the positions to the local file used when the database is disabled
+pub fn append(path: &str, positions: &[PositionRecord]) {
+    let lines: Vec<String> = positions
+    .iter()
+    .map(|position| serde_json::to_string(position).expect("Positions should be serializable"))
+    .collect();
+
+    if let Err(error) = crate::files::append_lines(path, &lines) {
+        tracing::error!(%error, path, "Couldn't save the positions")
+    }
+}
+
+/// Drops the oldest positions of the local file past `MAX_STORED_POSITIONS`
+pub fn rotate(path: &str) {
+    if let Err(error) = crate::files::rotate_lines(path, MAX_STORED_POSITIONS) {
+        tracing::error!(%error, path, "Couldn't rotate the positions")
+    }
+}
+
+/// Positions stored in `path` fetched after `since`, oldest first, optionally only the ones of `source`
+pub fn load(path: &str, source: Option<&Newspaper>, since: Option<chrono::DateTime<chrono::Utc>>) -> Vec<PositionRecord> {
+    read_all(path)
+    .into_iter()
+    .filter(|position| source.is_none_or(|source| &position.newspaper == source))
+    .filter(|position| since.is_none_or(|since| {
+        chrono::DateTime::parse_from_rfc3339(&position.fetched_at).is_ok_and(|fetched_at| fetched_at >= since)
+    }))
+    .collect()
+}
+
+/// How long a story was among the top positions of its source
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct TopStay {
+    pub newspaper: Newspaper,
+    pub title: String,
+    pub best_position: usize,
+    /// First and last fetch that found it there
+    pub first_seen: String,
+    pub last_seen: String,
+    pub snapshots: usize
+}
+
+impl TopStay {
+    pub fn duration(&self) -> chrono::Duration {
+        match (chrono::DateTime::parse_from_rfc3339(&self.first_seen), chrono::DateTime::parse_from_rfc3339(&self.last_seen)) {
+            (Ok(first_seen), Ok(last_seen)) => last_seen - first_seen,
+            _ => chrono::Duration::zero()
+        }
+    }
+}
+
+/// The stories that reached the first `top` positions of their source, the ones that stayed there longest first.
+/// A story that drops off the top and comes back later starts a new stay
+pub fn top_stays(positions: &[PositionRecord], top: usize) -> Vec<TopStay> {
+    let mut stays: Vec<TopStay> = vec![];
+    let mut indexes: HashMap<(Newspaper, &str), usize> = HashMap::new();
+    // Latest fetch of each source and the one before it
+    let mut fetches: HashMap<Newspaper, (&str, Option<&str>)> = HashMap::new();
+
+    for record in positions {
+        let fetch = fetches.entry(record.newspaper.clone()).or_insert((record.fetched_at.as_str(), None));
+
+        if fetch.0 != record.fetched_at {
+            *fetch = (record.fetched_at.as_str(), Some(fetch.0));
+        }
+
+        if record.position < 1 || record.position > top {
+            continue
+        }
+
+        let previous_fetch = fetch.1;
+        let key = (record.newspaper.clone(), record.story.as_str());
+
+        match indexes.get(&key) {
+            Some(index) if stays[*index].last_seen == record.fetched_at || Some(stays[*index].last_seen.as_str()) == previous_fetch => {
+                let stay = &mut stays[*index];
+                stay.best_position = stay.best_position.min(record.position);
+                stay.last_seen = record.fetched_at.clone();
+                stay.snapshots += 1;
+            },
+            _ => {
+                indexes.insert(key, stays.len());
+                stays.push(TopStay {
+                    newspaper: record.newspaper.clone(),
+                    title: record.title.clone(),
+                    best_position: record.position,
+                    first_seen: record.fetched_at.clone(),
+                    last_seen: record.fetched_at.clone(),
+                    snapshots: 1
+                });
+            }
+        }
+    }
+
+    stays.sort_by(|a, b| b.duration().cmp(&a.duration()).then(b.snapshots.cmp(&a.snapshots)));
+
+    stays
+}
+
+/// Share of the first `top` positions each section got in each source, biggest first, to compare what the outlets put on top
+pub fn section_shares(positions: &[PositionRecord], top: usize) -> Vec<(Newspaper, Vec<(Section, f64)>)> {
+    let mut counts: Vec<(Newspaper, HashMap<Section, usize>)> = vec![];
+
+    for record in positions.iter().filter(|record| record.position >= 1 && record.position <= top) {
+        let index = match counts.iter().position(|(newspaper, _)| *newspaper == record.newspaper) {
+            Some(index) => index,
+            None => {
+                counts.push((record.newspaper.clone(), HashMap::new()));
+                counts.len() - 1
+            }
+        };
+
+        *counts[index].1.entry(record.section).or_default() += 1;
+    }
+
+    counts.sort_by_key(|(newspaper, _)| Newspaper::ALL.iter().position(|ordered| ordered == newspaper));
+
+    counts.into_iter().map(|(newspaper, sections)| {
+        let total: usize = sections.values().sum();
+        let mut shares: Vec<(Section, f64)> = sections
+        .into_iter()
+        .map(|(section, count)| (section, count as f64 / total as f64))
+        .collect();
+
+        shares.sort_by(|a, b| b.1.total_cmp(&a.1).then(a.0.to_string().cmp(&b.0.to_string())));
+
+        (newspaper, shares)
+    }).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::sample_new;
+
+    /// A fetch of Página 12 listing the stories with these titles, from the top
+    fn listing(titles: [&str; 3]) -> Vec<JournalNew> {
+        titles.iter().enumerate().map(|(index, title)| JournalNew {
+            title: String::from(*title),
+            link: Some(format!("https://www.pagina12.com.ar/{title}")),
+            newspaper: Newspaper::PAGINA12,
+            section: Section::OTHER,
+            position: index + 1,
+            block: Some(String::from("apertura")),
+            ..sample_new()
+        }).collect()
+    }
+
+    #[test]
+    fn show_how_long_stories_stay_on_top() {
+        let path = std::env::temp_dir().join(format!("jornais_positions_test_{}.jsonl", std::process::id()));
+        let path = path.to_str().unwrap();
+        let _ = std::fs::remove_file(path);
+
+        append(path, &PositionRecord::snapshot(&listing(["politica", "economia", "deportes"]), "2024-05-01T10:00:00+00:00"));
+        append(path, &PositionRecord::snapshot(&listing(["economia", "politica", "deportes"]), "2024-05-01T10:20:00+00:00"));
+        append(path, &PositionRecord::snapshot(&listing(["deportes", "economia", "politica"]), "2024-05-01T11:00:00+00:00"));
+
+        let stored = load(path, Some(&Newspaper::PAGINA12), None);
+        assert_eq!(stored.len(), 9);
+        assert_eq!(stored[0].block.as_deref(), Some("apertura"));
+        assert!(load(path, Some(&Newspaper::CLARIN), None).is_empty());
+        assert_eq!(load(path, None, Some("2024-05-01T10:30:00Z".parse().unwrap())).len(), 3);
+
+        let top_stays = top_stays(&stored, 2);
+        let stays: Vec<(&str, i64, usize)> = top_stays
+        .iter()
+        .map(|stay| (stay.title.as_str(), stay.duration().num_minutes(), stay.best_position))
+        .collect();
+        assert_eq!(stays, [("economia", 60, 1), ("politica", 20, 1), ("deportes", 0, 1)]);
+
+        let shares = section_shares(&stored, 1);
+        assert_eq!(shares, [(Newspaper::PAGINA12, vec![(Section::OTHER, 1.0)])]);
+
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[test]
+    fn returning_to_the_top_starts_a_new_stay() {
+        let mut positions = vec![];
+        positions.extend(PositionRecord::snapshot(&listing(["politica", "economia", "deportes"]), "2024-05-01T10:00:00+00:00"));
+        positions.extend(PositionRecord::snapshot(&listing(["politica", "economia", "deportes"]), "2024-05-01T10:20:00+00:00"));
+        positions.extend(PositionRecord::snapshot(&listing(["economia", "deportes", "politica"]), "2024-05-01T10:40:00+00:00"));
+        positions.extend(PositionRecord::snapshot(&listing(["politica", "economia", "deportes"]), "2024-05-01T11:00:00+00:00"));
+
+        let top_stays = top_stays(&positions, 1);
+        let stays: Vec<(&str, i64, usize)> = top_stays
+        .iter()
+        .map(|stay| (stay.title.as_str(), stay.duration().num_minutes(), stay.snapshots))
+        .collect();
+        assert_eq!(stays, [("politica", 20, 2), ("economia", 0, 1), ("politica", 0, 1)]);
+    }
+}
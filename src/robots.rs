@@ -0,0 +1,151 @@
+/// The rules of a robots.txt that apply to one user agent
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Robots {
+    /// `(allowed, pattern)` pairs, patterns may use `*` and end with `$`
+    rules: Vec<(bool, String)>,
+    pub crawl_delay: Option<f64>
+}
+
+/// Whether `path` matches a robots.txt pattern, which is a prefix unless it ends with `$`
+fn matches(pattern: &str, path: &str) -> bool {
+    let (pattern, anchored) = match pattern.strip_suffix('$') {
+        Some(pattern) => (pattern, true),
+        None => (pattern, false)
+    };
+
+    let mut parts = pattern.split('*');
+    let first = parts.next().unwrap_or_default();
+
+    let mut rest = match path.strip_prefix(first) {
+        Some(rest) => rest,
+        None => return false
+    };
+
+    let parts: Vec<&str> = parts.collect();
+
+    for (index, part) in parts.iter().enumerate() {
+        // The last part of an anchored pattern has to be at the very end
+        if anchored && index == parts.len() - 1 {
+            return rest.ends_with(part)
+        }
+
+        match rest.find(part) {
+            Some(position) => rest = &rest[position + part.len()..],
+            None => return false
+        }
+    }
+
+    !anchored || (parts.is_empty() && rest.is_empty())
+}
+
+impl Robots {
+    /// What applies to a host whose robots.txt can't be reached or answers with a server error
+    pub fn disallow_all() -> Robots {
+        Robots { rules: vec![(false, String::from("/"))], crawl_delay: None }
+    }
+
+    /// Keeps the group of the most specific user agent contained in `user_agent`, or the `*` group
+    pub fn parse(text: &str, user_agent: &str) -> Robots {
+        let user_agent = user_agent.to_lowercase();
+        let mut groups: Vec<(Vec<String>, Robots)> = vec![];
+        let mut reading_agents = false;
+
+        for line in text.lines() {
+            let line = line.split('#').next().unwrap_or_default().trim();
+
+            let (field, value) = match line.split_once(':') {
+                Some((field, value)) => (field.trim().to_lowercase(), value.trim()),
+                None => continue
+            };
+
+            match field.as_str() {
+                "user-agent" => {
+                    if !reading_agents {
+                        groups.push((vec![], Robots::default()));
+                    }
+
+                    reading_agents = true;
+
+                    if let Some((agents, _)) = groups.last_mut() {
+                        agents.push(value.to_lowercase());
+                    }
+                },
+                "allow" | "disallow" | "crawl-delay" => {
+                    reading_agents = false;
+
+                    let robots = match groups.last_mut() {
+                        Some((_, robots)) => robots,
+                        None => continue
+                    };
+
+                    match field.as_str() {
+                        "crawl-delay" => robots.crawl_delay = value.parse().ok(),
+                        // An empty disallow allows everything
+                        "disallow" if value.is_empty() => {},
+                        rule => robots.rules.push((rule == "allow", String::from(value)))
+                    }
+                },
+                _ => {}
+            }
+        }
+
+        let specific = groups
+        .iter()
+        .flat_map(|(agents, robots)| agents.iter().map(move |agent| (agent, robots)))
+        .filter(|(agent, _)| agent.as_str() != "*" && user_agent.contains(agent.as_str()))
+        .max_by_key(|(agent, _)| agent.len())
+        .map(|(_, robots)| robots.clone());
+
+        specific
+        .or(groups.into_iter().find(|(agents, _)| agents.iter().any(|agent| agent == "*")).map(|(_, robots)| robots))
+        .unwrap_or_default()
+    }
+
+    /// The longest matching rule wins, allow wins ties, paths without rules are allowed
+    pub fn is_allowed(&self, path: &str) -> bool {
+        self.rules
+        .iter()
+        .filter(|(_, pattern)| matches(pattern, path))
+        .max_by_key(|(allowed, pattern)| (pattern.len(), *allowed))
+        .is_none_or(|(allowed, _)| *allowed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rules_apply_to_the_user_agent() {
+        let text = "
+            User-agent: *
+            Disallow: /privado/
+            Crawl-delay: 2
+
+            # Only for us
+            User-agent: otherbot
+            User-agent: jornais
+            Disallow: /buscar
+            Disallow: /*.pdf$
+            Allow: /buscar/ayuda
+            Crawl-delay: 5
+        ";
+
+        let robots = Robots::parse(text, "Mozilla/5.0 (compatible; jornais/0.1.0)");
+
+        assert_eq!(robots.crawl_delay, Some(5.0));
+        assert!(!robots.is_allowed("/buscar?q=rosario"));
+        assert!(robots.is_allowed("/buscar/ayuda"));
+        assert!(!robots.is_allowed("/ediciones/tapa.pdf"));
+        assert!(robots.is_allowed("/ediciones/tapa.pdf?descarga=1"));
+        assert!(robots.is_allowed("/privado/nota.html"));
+
+        let robots = Robots::parse(text, "curl/8.0");
+
+        assert_eq!(robots.crawl_delay, Some(2.0));
+        assert!(!robots.is_allowed("/privado/nota.html"));
+        assert!(robots.is_allowed("/buscar"));
+        assert!(Robots::parse("", "jornais").is_allowed("/"));
+        assert!(!Robots::disallow_all().is_allowed("/ultimas-noticias/?page=2"));
+    }
+}
@@ -0,0 +1,122 @@
+use serde::{Serialize, Deserialize};
+
+use crate::model::{JournalNew, Newspaper};
+
+pub const RUNS_FILE: &str = "jornais_runs.jsonl";
+
+/// Runs kept in the local file, the oldest ones are dropped past this
+const MAX_STORED_RUNS: usize = 5000;
+
+/// What fetching a source looked like, on the HTTP side and for the scraper
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct FetchStats {
+    pub started_at: String,
+    pub duration_ms: u64,
+    /// Status of the last response, `None` when the request never got one
+    pub http_status: Option<u16>,
+    /// Size of the response bodies as received, before decoding their charset, unchanged listings add nothing
+    pub bytes: usize,
+    /// Stories the scraper skipped for missing a title or a link
+    pub missing_titles: usize,
+    pub missing_links: usize
+}
+
+/// A single polling cycle of a single source
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct RunRecord {
+    pub newspaper: Newspaper,
+    pub started_at: String,
+    pub duration_ms: u64,
+    pub http_status: Option<u16>,
+    pub bytes: usize,
+    pub items: usize,
+    pub new_items: usize,
+    pub error: Option<String>
+}
+
+impl RunRecord {
+    pub fn new(newspaper: &Newspaper, result: &Result<Vec<JournalNew>, String>, stats: &FetchStats, new_items: usize) -> RunRecord {
+        RunRecord {
+            newspaper: newspaper.clone(),
+            started_at: stats.started_at.clone(),
+            duration_ms: stats.duration_ms,
+            http_status: stats.http_status,
+            bytes: stats.bytes,
+            items: result.as_ref().map(|news| news.len()).unwrap_or(0),
+            new_items,
+            error: result.as_ref().err().cloned()
+        }
+    }
+}
+
+/// Reads every run stored in `path`, oldest first
+fn read_all(path: &str) -> Vec<RunRecord> {
+    match std::fs::read_to_string(path) {
+        Ok(data) => data.lines().filter_map(|line| serde_json::from_str(line).ok()).collect(),
+        Err(_) => vec![]
+    }
+}
+
+/// Appends the runs to the local file used when the database is disabled
+pub fn append(path: &str, runs: &[RunRecord]) {
+    let lines: Vec<String> = runs
+    .iter()
+    .map(|run| serde_json::to_string(run).expect("Runs should be serializable"))
+    .collect();
+
+    if let Err(error) = crate::files::append_lines(path, &lines) {
+        tracing::error!(%error, path, "Couldn't save the runs")
+    }
+}
+
+/// Drops the oldest runs of the local file past `MAX_STORED_RUNS`
+pub fn rotate(path: &str) {
+    if let Err(error) = crate::files::rotate_lines(path, MAX_STORED_RUNS) {
+        tracing::error!(%error, path, "Couldn't rotate the runs")
+    }
+}
+
+/// Latest `limit` runs stored in `path`, newest first, optionally only the ones of `source`
+pub fn load(path: &str, source: Option<&Newspaper>, limit: usize) -> Vec<RunRecord> {
+    read_all(path)
+    .into_iter()
+    .rev()
+    .filter(|run| source.is_none_or(|source| &run.newspaper == source))
+    .take(limit)
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::sample_new;
+
+    #[test]
+    fn are_stored_newest_first() {
+        let path = std::env::temp_dir().join(format!("jornais_runs_test_{}.jsonl", std::process::id()));
+        let path = path.to_str().unwrap();
+        let _ = std::fs::remove_file(path);
+
+        let stats = FetchStats {
+            started_at: String::from("2024-05-01T10:00:00+00:00"),
+            duration_ms: 350,
+            http_status: Some(200),
+            bytes: 1024,
+            ..FetchStats::default()
+        };
+
+        let clarin = RunRecord::new(&Newspaper::CLARIN, &Ok(vec![sample_new()]), &stats, 1);
+        let tn = RunRecord::new(&Newspaper::TN, &Err(String::from("h2 should exist inside article")), &stats, 0);
+
+        append(path, std::slice::from_ref(&clarin));
+        append(path, std::slice::from_ref(&tn));
+
+        assert_eq!(clarin.items, 1);
+        assert_eq!(tn.error.as_deref(), Some("h2 should exist inside article"));
+        assert_eq!(load(path, None, 10), vec![tn, clarin.clone()]);
+        assert_eq!(load(path, Some(&Newspaper::CLARIN), 10), vec![clarin]);
+        assert_eq!(load(path, None, 1).len(), 1);
+
+        std::fs::remove_file(path).unwrap();
+    }
+}
@@ -0,0 +1,187 @@
+use std::collections::HashMap;
+
+const STOPWORDS: &[&str] = &[
+    "a", "al", "con", "de", "del", "el", "en", "es", "la", "las", "lo", "los", "o", "para",
+    "por", "que", "se", "su", "sus", "un", "una", "y"
+];
+
+/// Longest suffixes first so `aciones` wins over `es`
+const SUFFIXES: &[&str] = &[
+    "amientos", "imientos", "amiento", "imiento", "aciones", "uciones", "idades", "mente",
+    "acion", "ucion", "ismos", "istas", "ables", "ibles", "idad", "ismo", "ista", "able", "ible",
+    "osos", "osas", "ando", "iendo", "oso", "osa", "ces", "es", "as", "os", "s", "a", "o", "e"
+];
+
+const MIN_STEM_LENGTH: usize = 3;
+
+/// Stories the FULLTEXT index hands over to `rank` at most, the ones it scores best
+pub const MAX_CANDIDATES: u32 = 1000;
+
+/// Title matches weigh more than matches in the text
+const TITLE_WEIGHT: f64 = 2.0;
+
+fn fold_character(character: char) -> char {
+    match character {
+        'á' | 'à' | 'ä' | 'â' => 'a',
+        'é' | 'è' | 'ë' | 'ê' => 'e',
+        'í' | 'ì' | 'ï' | 'î' => 'i',
+        'ó' | 'ò' | 'ö' | 'ô' => 'o',
+        'ú' | 'ù' | 'ü' | 'û' => 'u',
+        'ñ' => 'n',
+        'ç' => 'c',
+        other => other
+    }
+}
+
+/// Lowercases, removes accents and replaces everything that isn't alphanumeric with spaces
+pub fn normalize(text: &str) -> String {
+    text
+    .to_lowercase()
+    .chars()
+    .map(fold_character)
+    .map(|character| if character.is_alphanumeric() { character } else { ' ' })
+    .collect()
+}
+
+/// Light Spanish stemmer that strips the most common derivational and plural suffixes
+pub fn stem(word: &str) -> String {
+    for suffix in SUFFIXES {
+        if let Some(stem) = word.strip_suffix(suffix) {
+            if stem.chars().count() >= MIN_STEM_LENGTH {
+                return String::from(stem)
+            }
+        }
+    }
+
+    String::from(word)
+}
+
+pub fn tokenize(text: &str) -> Vec<String> {
+    normalize(text)
+    .split_whitespace()
+    .filter(|word| !STOPWORDS.contains(word))
+    .map(stem)
+    .collect()
+}
+
+/// Ranks `(title, text)` documents against the query with BM25 and returns the index and score
+/// of each document matching at least one term, best first
+pub fn rank(query: &str, documents: &[(&str, &str)]) -> Vec<(usize, f64)> {
+    const K1: f64 = 1.2;
+    const B: f64 = 0.75;
+
+    let terms = tokenize(query);
+
+    let tokenized: Vec<(Vec<String>, Vec<String>)> = documents
+    .iter()
+    .map(|(title, text)| (tokenize(title), tokenize(text)))
+    .collect();
+
+    let average_length = tokenized.iter().map(|(title, text)| (title.len() + text.len()) as f64).sum::<f64>()
+        / tokenized.len().max(1) as f64;
+
+    let mut document_frequency: HashMap<&str, usize> = HashMap::new();
+
+    for term in &terms {
+        let frequency = tokenized.iter().filter(|(title, text)| title.contains(term) || text.contains(term)).count();
+        document_frequency.insert(term, frequency);
+    }
+
+    let mut ranked: Vec<(usize, f64)> = tokenized
+    .iter()
+    .enumerate()
+    .filter_map(|(index, (title, text))| {
+        let length = (title.len() + text.len()) as f64;
+
+        let score: f64 = terms.iter().map(|term| {
+            let frequency = title.iter().filter(|word| *word == term).count() as f64 * TITLE_WEIGHT
+                + text.iter().filter(|word| *word == term).count() as f64;
+
+            if frequency == 0.0 {
+                return 0.0
+            }
+
+            let matching = document_frequency[term.as_str()] as f64;
+            let idf = ((tokenized.len() as f64 - matching + 0.5) / (matching + 0.5) + 1.0).ln();
+
+            idf * frequency * (K1 + 1.0) / (frequency + K1 * (1.0 - B + B * length / average_length.max(1.0)))
+        }).sum();
+
+        if score > 0.0 { Some((index, score)) } else { None }
+    })
+    .collect();
+
+    ranked.sort_by(|a, b| b.1.total_cmp(&a.1));
+
+    ranked
+}
+
+/// Query for the FULLTEXT index of the news in boolean mode, a prefix search of each stem like `inflacion* rosari*`
+/// so the database returns the stories `rank` can score in any of their inflections. Empty when every word is a stopword.
+pub fn fulltext_query(query: &str) -> String {
+    tokenize(query).iter().map(|stem| format!("{stem}*")).collect::<Vec<String>>().join(" ")
+}
+
+/// Parses spans like `30m`, `12h`, `7d` or `2w`, the amount has to be positive
+pub fn parse_since(since: &str) -> Result<chrono::Duration, String> {
+    let invalid = || format!("{since} should look like 30m, 12h, 7d or 2w");
+    let (amount, unit) = since.split_at(since.char_indices().last().ok_or_else(invalid)?.0);
+    let amount: i64 = amount.parse().map_err(|_| invalid())?;
+
+    if amount <= 0 {
+        return Err(format!("{since} should be a positive span"))
+    }
+
+    let duration = match unit {
+        "m" => chrono::Duration::try_minutes(amount),
+        "h" => chrono::Duration::try_hours(amount),
+        "d" => chrono::Duration::try_days(amount),
+        "w" => chrono::Duration::try_weeks(amount),
+        _ => return Err(invalid())
+    };
+
+    duration.ok_or_else(|| format!("{since} is too long a span"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn folds_accents_and_stems() {
+        assert_eq!(normalize("INFLACIÓN, ¡Año!"), "inflacion   ano ");
+        assert_eq!(tokenize("Las inflaciones"), tokenize("la inflación"));
+        assert_eq!(tokenize("de la y"), Vec::<String>::new());
+    }
+
+    #[test]
+    fn ranks_title_matches_first() {
+        let documents = [
+            ("El dólar cerró estable", "Sin cambios en la city"),
+            ("Rosario: obras en el centro", "La inflación afecta los costos"),
+            ("La inflación de Rosario bajó", "Datos del INDEC"),
+        ];
+
+        let ranked = rank("inflación rosario", &documents);
+
+        assert_eq!(ranked.iter().map(|(index, _)| *index).collect::<Vec<usize>>(), vec![2, 1]);
+    }
+
+    #[test]
+    fn parses_since() {
+        assert_eq!(parse_since("7d"), Ok(chrono::Duration::days(7)));
+        assert_eq!(parse_since("12h"), Ok(chrono::Duration::hours(12)));
+        assert!(parse_since("7").is_err());
+        assert!(parse_since("7ñ").is_err());
+        assert!(parse_since("").is_err());
+        assert!(parse_since("0d").is_err());
+        assert!(parse_since("-3h").is_err());
+        assert!(parse_since(&format!("{}w", i64::MAX)).is_err());
+    }
+
+    #[test]
+    fn fulltext_query_searches_the_stems() {
+        assert_eq!(fulltext_query("La inflación de Rosario"), "infl* rosari*");
+        assert_eq!(fulltext_query("de la"), "");
+    }
+}
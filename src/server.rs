@@ -0,0 +1,215 @@
+use std::{net::SocketAddr, sync::Arc};
+
+use axum::{
+    extract::{Path, Query, State},
+    http::{header, StatusCode},
+    response::{IntoResponse, Response},
+    routing::{get, post},
+    Json, Router
+};
+use jornais::{classify::NewsFilter, images, health::{self, HealthHistory, SourceHealth}, metrics::Metrics, runs::{self, RunRecord}, state::{self, ReadingState, StarredNew, UserState}, model::{JournalNew, Newspaper, SourceInfo}};
+use serde::Deserialize;
+use sqlx::{MySql, Pool};
+use tokio::sync::Mutex;
+
+/// Stories of the last cycle of the HTML mode, served on `/api/news`
+pub const NEWS_FILE: &str = "jornais_news.json";
+
+#[derive(Clone)]
+struct ServerState {
+    default_user: String,
+    /// Serializes the load-modify-save cycles over the reading state file
+    state_lock: Arc<Mutex<()>>,
+    /// Where the runs are stored when the database is enabled, the local file is used otherwise
+    pool: Option<Pool<MySql>>
+}
+
+#[derive(Deserialize)]
+struct UserQuery {
+    user: Option<String>
+}
+
+#[derive(Deserialize)]
+struct RunsQuery {
+    source: Option<Newspaper>,
+    limit: Option<usize>
+}
+
+#[derive(Deserialize)]
+struct NewsQuery {
+    source: Option<Newspaper>,
+    /// Comma separated kinds like `live,video`
+    kind: Option<String>,
+    paywalled: Option<bool>,
+    /// Comma separated sections like `politics,economy`
+    section: Option<String>,
+    /// Comma separated authors like `Juan Pérez,María Gómez`
+    author: Option<String>
+}
+
+#[derive(Deserialize)]
+struct ReadRequest {
+    user: Option<String>,
+    link: String,
+    read: bool
+}
+
+#[derive(Deserialize)]
+struct StarRequest {
+    user: Option<String>,
+    title: String,
+    #[serde(default)]
+    text: String,
+    link: String,
+    newspaper: Newspaper,
+    starred: bool
+}
+
+async fn serve_file(path: &str, content_type: &'static str) -> Response {
+    match tokio::fs::read_to_string(path).await {
+        Ok(contents) => ([(header::CONTENT_TYPE, content_type)], contents).into_response(),
+        Err(_) => (StatusCode::NOT_FOUND, "The file wasn't generated yet").into_response()
+    }
+}
+
+/// Serves a cached thumbnail, only plain file names are accepted so nothing outside the directory is reachable
+async fn serve_thumbnail(Path(name): Path<String>) -> Response {
+    if !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '.') || name.starts_with('.') {
+        return StatusCode::NOT_FOUND.into_response()
+    }
+
+    match tokio::fs::read(std::path::Path::new(images::THUMBNAILS_DIR).join(name)).await {
+        Ok(contents) => ([(header::CONTENT_TYPE, "image/jpeg")], contents).into_response(),
+        Err(_) => StatusCode::NOT_FOUND.into_response()
+    }
+}
+
+async fn get_sources() -> Json<Vec<SourceInfo>> {
+    Json(Newspaper::ALL.iter().map(|newspaper| newspaper.info()).collect())
+}
+
+async fn get_health() -> Json<Vec<SourceHealth>> {
+    Json(HealthHistory::load(health::HEALTH_FILE).latest())
+}
+
+async fn get_runs(State(server): State<ServerState>, Query(query): Query<RunsQuery>) -> Json<Vec<RunRecord>> {
+    let limit = query.limit.unwrap_or(50);
+
+    match &server.pool {
+        Some(pool) => Json(crate::load_runs_from_database(pool, query.source.as_ref(), limit).await),
+        None => Json(runs::load(runs::RUNS_FILE, query.source.as_ref(), limit))
+    }
+}
+
+async fn get_news(Query(query): Query<NewsQuery>) -> Response {
+    let parsed = NewsFilter::parse_kinds(query.kind.as_deref().unwrap_or_default())
+    .and_then(|kinds| Ok((kinds, NewsFilter::parse_sections(query.section.as_deref().unwrap_or_default())?)));
+
+    let filter = match parsed {
+        Ok((kinds, sections)) => NewsFilter {
+            kinds,
+            paywalled: query.paywalled,
+            sections,
+            authors: NewsFilter::parse_authors(query.author.as_deref().unwrap_or_default())
+        },
+        Err(error) => return (StatusCode::BAD_REQUEST, error).into_response()
+    };
+
+    let news: Vec<JournalNew> = match tokio::fs::read_to_string(NEWS_FILE).await {
+        Ok(data) => serde_json::from_str(&data).unwrap_or_default(),
+        Err(_) => vec![]
+    };
+
+    Json(news
+    .into_iter()
+    .filter(|journal_new| query.source.as_ref().is_none_or(|source| journal_new.newspaper == *source) && filter.matches(journal_new))
+    .collect::<Vec<JournalNew>>()).into_response()
+}
+
+async fn get_state(State(server): State<ServerState>, Query(query): Query<UserQuery>) -> Json<UserState> {
+    let user = query.user.unwrap_or(server.default_user);
+
+    Json(ReadingState::load(state::STATE_FILE).user(&user))
+}
+
+async fn set_read(State(server): State<ServerState>, Json(request): Json<ReadRequest>) -> StatusCode {
+    let user = request.user.unwrap_or(server.default_user);
+    let _guard = server.state_lock.lock().await;
+
+    let mut reading_state = ReadingState::load(state::STATE_FILE);
+    reading_state.set_read(&user, &request.link, request.read);
+    reading_state.save(state::STATE_FILE);
+
+    StatusCode::NO_CONTENT
+}
+
+async fn set_starred(State(server): State<ServerState>, Json(request): Json<StarRequest>) -> StatusCode {
+    let user = request.user.unwrap_or(server.default_user);
+    let _guard = server.state_lock.lock().await;
+
+    let starred_new = StarredNew {
+        title: request.title,
+        text: request.text,
+        link: request.link,
+        newspaper: request.newspaper,
+        starred_on: chrono::Utc::now().to_rfc3339()
+    };
+
+    let mut reading_state = ReadingState::load(state::STATE_FILE);
+    reading_state.set_starred(&user, starred_new, request.starred);
+    reading_state.save(state::STATE_FILE);
+
+    StatusCode::NO_CONTENT
+}
+
+/// Serves the metrics of the polling loops in the Prometheus text format
+pub async fn serve_metrics(address: String, metrics: Arc<Metrics>) {
+    let socket: SocketAddr = address.parse().expect("The metrics address should look like 127.0.0.1:9090");
+
+    let app = Router::new()
+    .route("/metrics", get(|State(metrics): State<Arc<Metrics>>| async move {
+        ([(header::CONTENT_TYPE, "text/plain; version=0.0.4")], metrics.render())
+    }))
+    .with_state(metrics);
+
+    tracing::info!("Serving metrics on http://{address}/metrics");
+
+    if let Err(error) = axum::Server::bind(&socket).serve(app.into_make_service()).await {
+        tracing::error!(%error, "The metrics server stopped")
+    }
+}
+
+/// Serves the generated dashboard and feed together with the API used by the dashboard,
+/// reading the runs from the database when there is a `pool`. The dashboard, the feed and `/api/news`
+/// are outputs of the HTML mode, so they aren't mounted when the database is enabled
+pub async fn serve(address: String, default_user: String, pool: Option<Pool<MySql>>) {
+    let socket: SocketAddr = address.parse().expect("The server address should look like 127.0.0.1:8080");
+
+    let mut app = Router::new()
+    .route(&format!("/{}/:name", images::THUMBNAILS_DIR), get(serve_thumbnail))
+    .route("/api/sources", get(get_sources))
+    .route("/api/health", get(get_health))
+    .route("/api/runs", get(get_runs))
+    .route("/api/state", get(get_state))
+    .route("/api/read", post(set_read))
+    .route("/api/starred", post(set_starred));
+
+    if pool.is_none() {
+        app = app
+        .route("/", get(|| serve_file("jornais.html", "text/html; charset=utf-8")))
+        .route("/feed.xml", get(|| serve_file("jornais.xml", "application/atom+xml")))
+        .route("/api/news", get(get_news));
+    }
+
+    let app = app.with_state(ServerState {
+        default_user,
+        state_lock: Arc::new(Mutex::new(())),
+        pool
+    });
+
+    tracing::info!("Serving the dashboard on http://{address}");
+
+    if let Err(error) = axum::Server::bind(&socket).serve(app.into_make_service()).await {
+        tracing::error!(%error, "The dashboard server stopped")
+    }
+}
@@ -0,0 +1,68 @@
+use tokio::{signal, sync::mpsc};
+
+#[allow(clippy::upper_case_acronyms)]
+pub enum Signal {
+    /// Ctrl+C or SIGTERM, finish the current cycle and exit
+    SHUTDOWN,
+    /// SIGHUP, load the config file again
+    RELOAD
+}
+
+#[cfg(unix)]
+async fn listen_unix(sender: mpsc::UnboundedSender<Signal>) -> std::io::Result<()> {
+    use signal::unix::{signal, SignalKind};
+
+    let mut terminate = signal(SignalKind::terminate())?;
+    let mut hangup = signal(SignalKind::hangup())?;
+    let mut shutting_down = false;
+
+    loop {
+        let received = tokio::select! {
+            _ = signal::ctrl_c() => Signal::SHUTDOWN,
+            _ = terminate.recv() => Signal::SHUTDOWN,
+            _ = hangup.recv() => Signal::RELOAD
+        };
+
+        if let Signal::SHUTDOWN = received {
+            if shutting_down {
+                tracing::warn!("Received a second shutdown signal, exiting right away");
+                std::process::exit(1)
+            }
+
+            shutting_down = true;
+        }
+
+        if sender.send(received).is_err() {
+            return Ok(())
+        }
+    }
+}
+
+#[cfg(not(unix))]
+async fn listen_ctrl_c(sender: mpsc::UnboundedSender<Signal>) -> std::io::Result<()> {
+    signal::ctrl_c().await?;
+    let _ = sender.send(Signal::SHUTDOWN);
+
+    signal::ctrl_c().await?;
+    tracing::warn!("Received a second shutdown signal, exiting right away");
+    std::process::exit(1)
+}
+
+/// Forwards the shutdown and reload signals to the watch loops.
+/// A second shutdown signal exits right away without waiting for the current cycle.
+pub fn listen() -> mpsc::UnboundedReceiver<Signal> {
+    let (sender, receiver) = mpsc::unbounded_channel();
+
+    tokio::spawn(async move {
+        #[cfg(unix)]
+        let result = listen_unix(sender).await;
+        #[cfg(not(unix))]
+        let result = listen_ctrl_c(sender).await;
+
+        if let Err(error) = result {
+            tracing::error!(%error, "Couldn't listen for signals")
+        }
+    });
+
+    receiver
+}
@@ -0,0 +1,167 @@
+use std::collections::{HashMap, HashSet};
+
+use serde::{Serialize, Deserialize};
+
+use crate::model::{JournalNew, Newspaper};
+
+/// Where the reading state is kept in every mode, it isn't stored in the database with the news
+/// so it stays on the machine that runs the dashboard, the TUI or the `starred` command
+pub const STATE_FILE: &str = "jornais_state.json";
+
+/// Query parameters added by sharing and ad links, which never change the story a link points to
+const TRACKING_PARAMETERS: [&str; 2] = ["fbclid", "gclid"];
+
+fn is_tracking_parameter(name: &str) -> bool {
+    name.starts_with("utm_") || TRACKING_PARAMETERS.contains(&name)
+}
+
+/// Strips the fragment, tracking parameters and trailing slash so the same story shared from
+/// different places still maps to a single key. Keys that aren't URLs are only trimmed
+pub fn canonical_link(link: &str) -> String {
+    let link = link.trim();
+
+    let Ok(mut url) = reqwest::Url::parse(link) else {
+        return String::from(link.trim_end_matches('/'))
+    };
+
+    let query: Vec<(String, String)> = url
+    .query_pairs()
+    .filter(|(name, _)| !is_tracking_parameter(name))
+    .map(|(name, value)| (name.into_owned(), value.into_owned()))
+    .collect();
+
+    url.set_fragment(None);
+    url.set_query(None);
+
+    if !query.is_empty() {
+        url.query_pairs_mut().extend_pairs(&query);
+    }
+
+    let query = url.query().map(|query| format!("?{query}")).unwrap_or_default();
+    url.set_query(None);
+
+    format!("{}{query}", url.as_str().trim_end_matches('/'))
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Eq, PartialEq)]
+pub struct StarredNew {
+    pub title: String,
+    pub text: String,
+    pub link: String,
+    pub newspaper: Newspaper,
+    pub starred_on: String
+}
+
+impl From<&JournalNew> for StarredNew {
+    fn from(journal_new: &JournalNew) -> Self {
+        StarredNew {
+            title: journal_new.title.clone(),
+            text: journal_new.text.clone(),
+            link: journal_new.key(),
+            newspaper: journal_new.newspaper.clone(),
+            starred_on: chrono::Utc::now().to_rfc3339()
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(default)]
+pub struct UserState {
+    pub read: HashSet<String>,
+    pub starred: Vec<StarredNew>
+}
+
+/// Read and starred flags of every user, keyed by the canonical link of each story
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(default)]
+pub struct ReadingState {
+    pub users: HashMap<String, UserState>
+}
+
+impl ReadingState {
+    pub fn load(path: &str) -> ReadingState {
+        match std::fs::read_to_string(path) {
+            Ok(data) => serde_json::from_str(&data).unwrap_or_else(|error| {
+                tracing::error!(%error, path, "Couldn't parse the reading state, starting from an empty one");
+                ReadingState::default()
+            }),
+            Err(_) => ReadingState::default()
+        }
+    }
+
+    pub fn save(&self, path: &str) {
+        let data = serde_json::to_string_pretty(self).expect("The reading state should be serializable");
+
+        if let Err(error) = crate::files::write_atomically(path, data) {
+            tracing::error!(%error, path, "Couldn't save the reading state")
+        }
+    }
+
+    pub fn user(&self, user: &str) -> UserState {
+        self.users.get(user).cloned().unwrap_or_default()
+    }
+
+    pub fn is_read(&self, user: &str, link: &str) -> bool {
+        self.users.get(user).is_some_and(|state| state.read.contains(&canonical_link(link)))
+    }
+
+    pub fn is_starred(&self, user: &str, link: &str) -> bool {
+        let link = canonical_link(link);
+
+        self.users.get(user).is_some_and(|state| state.starred.iter().any(|starred| starred.link == link))
+    }
+
+    pub fn set_read(&mut self, user: &str, link: &str, read: bool) {
+        let state = self.users.entry(String::from(user)).or_default();
+
+        if read {
+            state.read.insert(canonical_link(link));
+        } else {
+            state.read.remove(&canonical_link(link));
+        }
+    }
+
+    pub fn set_starred(&mut self, user: &str, mut starred_new: StarredNew, starred: bool) {
+        starred_new.link = canonical_link(&starred_new.link);
+        let state = self.users.entry(String::from(user)).or_default();
+
+        state.starred.retain(|starred| starred.link != starred_new.link);
+
+        if starred {
+            state.starred.push(starred_new);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::sample_new;
+
+    #[test]
+    fn reading_state_is_per_user_and_canonical() {
+        let mut reading_state = ReadingState::default();
+        let journal_new = sample_new();
+
+        reading_state.set_read("ana", "https://www.clarin.com/politica/nota.html?utm_source=twitter#comments", true);
+        reading_state.set_starred("ana", StarredNew::from(&journal_new), true);
+        reading_state.set_starred("ana", StarredNew::from(&journal_new), true);
+
+        assert!(reading_state.is_read("ana", &journal_new.key()));
+        assert!(!reading_state.is_read("luis", &journal_new.key()));
+        assert!(reading_state.is_starred("ana", "https://www.clarin.com/politica/nota.html/"));
+        assert_eq!(reading_state.user("ana").starred.len(), 1);
+        assert!(!reading_state.is_read("ana", "https://www.clarin.com/politica/nota.html?page=2"));
+
+        reading_state.set_starred("ana", StarredNew::from(&journal_new), false);
+
+        assert!(!reading_state.is_starred("ana", &journal_new.key()));
+    }
+
+    #[test]
+    fn only_tracking_parameters_are_stripped() {
+        assert_eq!(canonical_link("https://www.lanacion.com.ar/nota/?fbclid=x&id=7&utm_medium=social#top"), "https://www.lanacion.com.ar/nota?id=7");
+        assert_eq!(canonical_link("https://www.lanacion.com.ar/nota?gclid=x"), "https://www.lanacion.com.ar/nota");
+        assert_eq!(canonical_link(" Título sin link/ "), "Título sin link");
+    }
+}
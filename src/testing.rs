@@ -0,0 +1,102 @@
+//! Helpers shared by the tests of the modules
+
+use std::sync::{atomic::{AtomicUsize, Ordering}, Arc};
+
+use tokio::{io::{AsyncReadExt, AsyncWriteExt}, net::TcpListener};
+
+use crate::{http, model::{ContentKind, JournalNew, Newspaper, Section}};
+
+pub fn http_client() -> http::HttpClient {
+    http::HttpClient::new(&http::HttpConfig { host_interval_ms: 0, ..http::HttpConfig::default() }).unwrap()
+}
+
+/// Accepts `responses.len()` requests on a local port, answering each one with the given status,
+/// and returns the address together with a handle that resolves to the received (headers, body) pairs
+pub async fn webhook_receiver(responses: Vec<u16>) -> (String, tokio::task::JoinHandle<Vec<(String, String)>>) {
+    http_server(responses.into_iter().map(|status| format!("HTTP/1.1 {status} OK\r\ncontent-length: 0\r\nconnection: close\r\n\r\n")).collect()).await
+}
+
+/// Like `webhook_receiver` but answering with the given raw responses
+pub async fn http_server(responses: Vec<String>) -> (String, tokio::task::JoinHandle<Vec<(String, String)>>) {
+    http_server_with_robots(None, responses).await
+}
+
+/// Like `http_server` but answering every `/robots.txt` request with `robots`, or a 404 without it,
+/// without using one of the responses
+pub async fn http_server_with_robots(robots: Option<&str>, responses: Vec<String>) -> (String, tokio::task::JoinHandle<Vec<(String, String)>>) {
+    let robots_response = match robots {
+        Some(robots) => format!("HTTP/1.1 200 OK\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{robots}", robots.len()),
+        None => String::from("HTTP/1.1 404 Not Found\r\ncontent-length: 0\r\nconnection: close\r\n\r\n")
+    };
+    let (address, handle, _) = http_server_with_robots_response(robots_response, responses).await;
+
+    (address, handle)
+}
+
+/// Like `http_server_with_robots` but answering `/robots.txt` with the raw `robots_response`,
+/// also returns how many times the robots.txt was asked for
+pub async fn http_server_with_robots_response(robots_response: String, responses: Vec<String>) -> (String, tokio::task::JoinHandle<Vec<(String, String)>>, Arc<AtomicUsize>) {
+    let robots_requests = Arc::new(AtomicUsize::new(0));
+    let counted = robots_requests.clone();
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let address = format!("http://{}/hook", listener.local_addr().unwrap());
+
+    let handle = tokio::spawn(async move {
+        let mut received = vec![];
+
+        let mut responses = responses.into_iter().peekable();
+
+        while let Some(response) = responses.peek() {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut request = vec![];
+            let mut buffer = [0; 4096];
+
+            let (headers, body) = loop {
+                let read = socket.read(&mut buffer).await.unwrap();
+                request.extend_from_slice(&buffer[..read]);
+                let text = String::from_utf8_lossy(&request).to_string();
+
+                if let Some((headers, body)) = text.split_once("\r\n\r\n") {
+                    let length = headers
+                    .lines()
+                    .find_map(|line| line.to_lowercase().strip_prefix("content-length: ").map(|l| l.parse::<usize>().unwrap()))
+                    .unwrap_or(0);
+
+                    if body.len() >= length {
+                        break (String::from(headers), String::from(body))
+                    }
+                }
+            };
+
+            if headers.starts_with("GET /robots.txt ") {
+                counted.fetch_add(1, Ordering::SeqCst);
+                socket.write_all(robots_response.as_bytes()).await.unwrap();
+                continue
+            }
+
+            socket.write_all(response.as_bytes()).await.unwrap();
+            received.push((headers, body));
+            responses.next();
+        }
+
+        received
+    });
+
+    (address, handle, robots_requests)
+}
+
+pub fn sample_new() -> JournalNew {
+    JournalNew {
+        title: String::from("Título"),
+        text: String::from("Texto"),
+        link: Some(String::from("https://www.clarin.com/politica/nota.html")),
+        newspaper: Newspaper::CLARIN,
+        kind: ContentKind::ARTICLE,
+        paywalled: false,
+        section: Section::POLITICS,
+        authors: vec![],
+        image: None,
+        position: 1,
+        block: None
+    }
+}
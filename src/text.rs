@@ -0,0 +1,156 @@
+use encoding_rs::{Encoding, UTF_8, WINDOWS_1252};
+use unicode_normalization::{char::is_combining_mark, UnicodeNormalization};
+
+/// How much of the start of a page is searched for a `<meta>` charset
+const META_SNIFF_BYTES: usize = 4096;
+
+/// Named entities the sources use, the numeric ones are decoded separately
+const ENTITIES: [(&str, char); 48] = [
+    ("amp", '&'), ("lt", '<'), ("gt", '>'), ("quot", '"'), ("apos", '\''), ("nbsp", '\u{a0}'),
+    ("aacute", 'á'), ("eacute", 'é'), ("iacute", 'í'), ("oacute", 'ó'), ("uacute", 'ú'),
+    ("Aacute", 'Á'), ("Eacute", 'É'), ("Iacute", 'Í'), ("Oacute", 'Ó'), ("Uacute", 'Ú'),
+    ("ntilde", 'ñ'), ("Ntilde", 'Ñ'), ("uuml", 'ü'), ("Uuml", 'Ü'),
+    ("agrave", 'à'), ("egrave", 'è'), ("ograve", 'ò'), ("ccedil", 'ç'), ("Ccedil", 'Ç'),
+    ("iexcl", '¡'), ("iquest", '¿'), ("laquo", '«'), ("raquo", '»'), ("ordm", 'º'), ("ordf", 'ª'),
+    ("ldquo", '“'), ("rdquo", '”'), ("lsquo", '‘'), ("rsquo", '’'), ("sbquo", '‚'), ("bdquo", '„'),
+    ("hellip", '…'), ("mdash", '—'), ("ndash", '–'), ("middot", '·'), ("bull", '•'),
+    ("deg", '°'), ("euro", '€'), ("copy", '©'), ("reg", '®'), ("shy", '\u{ad}'), ("zwj", '\u{200d}')
+];
+
+/// The charset named by a `Content-Type` value or the attributes of a `<meta>` tag, like `text/html; charset=ISO-8859-1`
+fn charset_of(value: &str) -> Option<&'static Encoding> {
+    let value = value.to_ascii_lowercase();
+    let (_, charset) = value.split_once("charset=")?;
+    let charset = charset.trim_start_matches(['"', '\'', ' ']);
+    let end = charset.find(|c: char| !(c.is_ascii_alphanumeric() || c == '-' || c == '_')).unwrap_or(charset.len());
+
+    Encoding::for_label(&charset.as_bytes()[..end])
+}
+
+/// Looks for `<meta charset="...">` or `<meta http-equiv="Content-Type" content="...; charset=...">` near the start of the page
+fn meta_charset(body: &[u8]) -> Option<&'static Encoding> {
+    let start = String::from_utf8_lossy(&body[..body.len().min(META_SNIFF_BYTES)]).to_ascii_lowercase();
+
+    start
+    .split("<meta")
+    .skip(1)
+    .filter_map(|tag| tag.split('>').next())
+    .find_map(charset_of)
+}
+
+/// Decodes a page using its byte order mark, then the charset of the `Content-Type` header and then the one of its `<meta>` tags.
+/// Pages without any of them are read as UTF-8, or as Windows-1252 when they aren't valid UTF-8.
+pub fn decode_html(body: &[u8], content_type: Option<&str>) -> String {
+    let encoding = Encoding::for_bom(body)
+    .map(|(encoding, _)| encoding)
+    .or_else(|| content_type.and_then(charset_of))
+    .or_else(|| meta_charset(body))
+    .unwrap_or(if std::str::from_utf8(body).is_ok() { UTF_8 } else { WINDOWS_1252 });
+
+    let (text, _, _) = encoding.decode(body);
+
+    text.into_owned()
+}
+
+/// Decodes named entities like `&quot;` and numeric ones like `&#243;` or `&#xF3;`, leaving unknown ones as they are
+pub fn decode_entities(text: &str) -> String {
+    let mut decoded = String::with_capacity(text.len());
+    let mut rest = text;
+
+    while let Some(start) = rest.find('&') {
+        decoded.push_str(&rest[..start]);
+        rest = &rest[start..];
+
+        let entity = rest[1..].find(';').filter(|end| *end <= 32).map(|end| &rest[1..end + 1]);
+
+        let character = entity.and_then(|entity| match entity.strip_prefix('#') {
+            Some(number) => match number.strip_prefix(['x', 'X']) {
+                Some(hex) => u32::from_str_radix(hex, 16).ok(),
+                None => number.parse().ok()
+            }.and_then(char::from_u32),
+            None => ENTITIES.iter().find(|(name, _)| *name == entity).map(|(_, character)| *character)
+        });
+
+        match (entity, character) {
+            (Some(entity), Some(character)) => {
+                decoded.push(character);
+                rest = &rest[entity.len() + 2..];
+            },
+            _ => {
+                decoded.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+
+    decoded.push_str(rest);
+    decoded
+}
+
+/// Characters that don't show but break comparisons, like zero width spaces and soft hyphens
+fn is_invisible(character: char) -> bool {
+    matches!(character, '\u{ad}' | '\u{200b}'..='\u{200f}' | '\u{2060}' | '\u{feff}' | '\u{202a}'..='\u{202e}')
+    || (character.is_control() && !character.is_whitespace())
+}
+
+/// Turns scraped text into what is stored and shown: entities decoded, invisible characters removed,
+/// every run of whitespace collapsed into one space and the result in Unicode NFC
+pub fn clean(text: &str) -> String {
+    decode_entities(text)
+    .chars()
+    .filter(|character| !is_invisible(*character))
+    .collect::<String>()
+    .split_whitespace()
+    .collect::<Vec<&str>>()
+    .join(" ")
+    .nfc()
+    .collect()
+}
+
+/// Lowercase, without accents and with dashes instead of spaces, so `Información General` becomes `informacion-general`
+pub fn slug(text: &str) -> String {
+    text
+    .trim()
+    .to_lowercase()
+    .nfd()
+    .filter(|c| !is_combining_mark(*c))
+    .collect::<String>()
+    .split_whitespace()
+    .collect::<Vec<&str>>()
+    .join("-")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn entities_are_decoded() {
+        assert_eq!(decode_entities("&quot;Econom&#xED;a&quot; &amp; pol&#237;tica &copy &unknown;"), "\"Economía\" & política &copy &unknown;");
+        assert_eq!(decode_entities("/1-nota?a=1&amp;b=2"), "/1-nota?a=1&b=2");
+    }
+
+    #[test]
+    fn scraped_text_is_cleaned() {
+        assert_eq!(clean("\n   Inflaci\u{f3}n:&nbsp;el  dato\u{200b} de  \t septiembre\u{ad}\n"), "Inflación: el dato de septiembre");
+        assert_eq!(clean("Debate&#x20;en  Diputados&hellip;"), "Debate en Diputados…");
+        // "ó" written as "o" followed by a combining accent ends up as the single character
+        assert_eq!(clean("Co\u{301}rdoba"), "Córdoba");
+    }
+
+    #[test]
+    fn pages_are_decoded_with_their_charset() {
+        let latin1 = b"<html><head><meta http-equiv=\"Content-Type\" content=\"text/html; charset=ISO-8859-1\"></head>Pe\xf1a</html>";
+
+        assert!(decode_html(latin1, None).ends_with("Peña</html>"));
+        assert!(decode_html(latin1, Some("text/html; charset=utf-8")).ends_with("Pe\u{fffd}a</html>"));
+        assert_eq!(decode_html("<meta charset=\"utf-8\">Peña".as_bytes(), Some("text/html")), "<meta charset=\"utf-8\">Peña");
+        assert_eq!(decode_html(b"Pe\xf1a", None), "Peña");
+    }
+
+    #[test]
+    fn slugs_ignore_case_accents_and_spacing() {
+        assert_eq!(slug("  Información   General "), "informacion-general");
+        assert_eq!(slug("JUAN PÉREZ"), slug("juan perez"));
+    }
+}
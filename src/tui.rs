@@ -0,0 +1,296 @@
+use std::{process::Stdio, sync::mpsc, time::Duration};
+
+use jornais::{newspapers, classify::NewsFilter, http::HttpClient, state::{self, ReadingState, StarredNew}, model::{ContentKind, JournalNew, Newspaper, Region}};
+use ratatui::{
+    crossterm::event::{self, Event, KeyCode, KeyEventKind},
+    layout::{Constraint, Layout},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, List, ListItem, ListState, Paragraph, Tabs},
+    DefaultTerminal, Frame
+};
+use tokio::{task, time};
+
+struct App {
+    sources: Vec<(Newspaper, Vec<JournalNew>)>,
+    tab: usize,
+    list_state: ListState,
+    user: String,
+    reading_state: ReadingState,
+    search: String,
+    searching: bool,
+    last_update: Option<chrono::DateTime<chrono::Local>>
+}
+
+impl App {
+    fn new(user: String) -> App {
+        App {
+            sources: vec![],
+            tab: 0,
+            list_state: ListState::default(),
+            user,
+            reading_state: ReadingState::load(state::STATE_FILE),
+            search: String::new(),
+            searching: false,
+            last_update: None
+        }
+    }
+
+    /// The first tab shows every newspaper, the rest one newspaper each
+    fn tab_titles(&self) -> Vec<String> {
+        let mut titles = vec![String::from("all")];
+
+        for (newspaper, news) in &self.sources {
+            let unread = news.iter().filter(|journal_new| !self.reading_state.is_read(&self.user, &journal_new.key())).count();
+            titles.push(format!("{} ({unread})", newspaper.info().name));
+        }
+
+        titles
+    }
+
+    fn visible(&self) -> Vec<&JournalNew> {
+        let search = self.search.to_lowercase();
+
+        self.sources
+        .iter()
+        .enumerate()
+        .filter(|(index, _)| self.tab == 0 || self.tab == index + 1)
+        .flat_map(|(_, (_, news))| news.iter())
+        .filter(|journal_new| {
+            search.is_empty()
+            || journal_new.title.to_lowercase().contains(&search)
+            || journal_new.text.to_lowercase().contains(&search)
+        })
+        .collect()
+    }
+
+    fn select_tab(&mut self, tab: usize) {
+        self.tab = tab;
+        self.list_state.select(if self.visible().is_empty() { None } else { Some(0) });
+    }
+
+    fn move_selection(&mut self, offset: isize) {
+        let length = self.visible().len();
+
+        if length == 0 {
+            self.list_state.select(None);
+            return
+        }
+
+        let current = self.list_state.selected().unwrap_or(0) as isize;
+        self.list_state.select(Some((current + offset).clamp(0, length as isize - 1) as usize));
+    }
+
+    fn selected(&self) -> Option<JournalNew> {
+        self.list_state.selected().and_then(|index| self.visible().get(index).map(|journal_new| (*journal_new).clone()))
+    }
+
+    /// Reloads the reading state before changing it so flags set from the dashboard aren't lost
+    fn update_reading_state(&mut self, change: impl FnOnce(&mut ReadingState, &str)) {
+        self.reading_state = ReadingState::load(state::STATE_FILE);
+        change(&mut self.reading_state, &self.user);
+        self.reading_state.save(state::STATE_FILE);
+    }
+
+    fn open_selected(&mut self) {
+        if let Some(journal_new) = self.selected() {
+            if let Some(link) = &journal_new.link {
+                open_in_browser(link);
+            }
+
+            self.update_reading_state(|reading_state, user| reading_state.set_read(user, &journal_new.key(), true));
+        }
+    }
+
+    fn toggle_star_selected(&mut self) {
+        if let Some(journal_new) = self.selected() {
+            let starred = !self.reading_state.is_starred(&self.user, &journal_new.key());
+
+            self.update_reading_state(|reading_state, user| reading_state.set_starred(user, StarredNew::from(&journal_new), starred));
+        }
+    }
+
+    fn update(&mut self, sources: Vec<(Newspaper, Vec<JournalNew>)>) {
+        self.sources = sources;
+        self.last_update = Some(chrono::Local::now());
+        self.move_selection(0);
+    }
+}
+
+fn open_in_browser(link: &str) {
+    let command = if cfg!(target_os = "macos") {
+        "open"
+    } else if cfg!(target_os = "windows") {
+        "explorer"
+    } else {
+        "xdg-open"
+    };
+
+    let _ = std::process::Command::new(command)
+    .arg(link)
+    .stdout(Stdio::null())
+    .stderr(Stdio::null())
+    .spawn();
+}
+
+/// Marks the stories that aren't plain free articles, like `[En vivo] [$] `
+fn badges(journal_new: &JournalNew) -> String {
+    let mut badges = String::new();
+
+    if journal_new.kind != ContentKind::ARTICLE {
+        badges += &format!("[{}] ", journal_new.kind.name());
+    }
+
+    if journal_new.paywalled {
+        badges += "[$] ";
+    }
+
+    badges
+}
+
+fn draw(frame: &mut Frame, app: &mut App) {
+    let [tabs_area, list_area, status_area] = Layout::vertical([
+        Constraint::Length(3),
+        Constraint::Min(1),
+        Constraint::Length(3)
+    ]).areas(frame.area());
+
+    let tabs = Tabs::new(app.tab_titles())
+    .block(Block::default().borders(Borders::ALL).title(" jornais "))
+    .select(app.tab)
+    .highlight_style(Style::default().fg(Color::Green).add_modifier(Modifier::BOLD));
+
+    frame.render_widget(tabs, tabs_area);
+
+    let items: Vec<ListItem> = app.visible().iter().map(|journal_new| {
+        let marker = if app.reading_state.is_read(&app.user, &journal_new.key()) {
+            Span::raw("  ")
+        } else {
+            Span::styled("● ", Style::default().fg(Color::Yellow))
+        };
+
+        let star = if app.reading_state.is_starred(&app.user, &journal_new.key()) {
+            Span::styled("★ ", Style::default().fg(Color::Yellow))
+        } else {
+            Span::raw("  ")
+        };
+
+        let mut lines = vec![Line::from(vec![
+            marker,
+            star,
+            Span::styled(format!("[{}] ", journal_new.newspaper), Style::default().fg(Color::DarkGray)),
+            Span::styled(badges(journal_new), Style::default().fg(Color::Magenta)),
+            Span::styled(journal_new.title.clone(), Style::default().fg(Color::Blue).add_modifier(Modifier::BOLD))
+        ])];
+
+        if !journal_new.text.is_empty() {
+            lines.push(Line::from(Span::styled(format!("  {}", journal_new.text), Style::default().fg(Color::Gray))));
+        }
+
+        ListItem::new(lines)
+    }).collect();
+
+    let list = List::new(items)
+    .block(Block::default().borders(Borders::ALL))
+    .highlight_style(Style::default().bg(Color::DarkGray))
+    .highlight_symbol("> ");
+
+    frame.render_stateful_widget(list, list_area, &mut app.list_state);
+
+    let status = if app.searching {
+        format!("/{}", app.search)
+    } else {
+        let updated = match app.last_update {
+            Some(last_update) => format!("updated {}", last_update.format("%d-%m-%Y %H:%M")),
+            None => String::from("loading...")
+        };
+
+        format!("←/→ newspaper  ↑/↓ move  Enter open  s star  / search  q quit  |  {updated}")
+    };
+
+    frame.render_widget(Paragraph::new(status).block(Block::default().borders(Borders::ALL)), status_area);
+}
+
+/// Returns `false` when the user asked to quit
+fn handle_key(app: &mut App, key: KeyCode) -> bool {
+    if app.searching {
+        match key {
+            KeyCode::Enter => app.searching = false,
+            KeyCode::Esc => {
+                app.searching = false;
+                app.search.clear();
+            },
+            KeyCode::Backspace => { app.search.pop(); },
+            KeyCode::Char(character) => app.search.push(character),
+            _ => {}
+        }
+
+        app.select_tab(app.tab);
+        return true
+    }
+
+    match key {
+        KeyCode::Char('q') | KeyCode::Esc => return false,
+        KeyCode::Char('/') => app.searching = true,
+        KeyCode::Right | KeyCode::Tab => app.select_tab((app.tab + 1) % (app.sources.len() + 1)),
+        KeyCode::Left | KeyCode::BackTab => app.select_tab((app.tab + app.sources.len()) % (app.sources.len() + 1)),
+        KeyCode::Down => app.move_selection(1),
+        KeyCode::Up => app.move_selection(-1),
+        KeyCode::PageDown => app.move_selection(10),
+        KeyCode::PageUp => app.move_selection(-10),
+        KeyCode::Enter => app.open_selected(),
+        KeyCode::Char('s') => app.toggle_star_selected(),
+        _ => {}
+    }
+
+    true
+}
+
+fn ui_loop(mut terminal: DefaultTerminal, user: String, updates: mpsc::Receiver<Vec<(Newspaper, Vec<JournalNew>)>>) -> std::io::Result<()> {
+    let mut app = App::new(user);
+
+    loop {
+        if let Ok(sources) = updates.try_recv() {
+            app.update(sources);
+        }
+
+        terminal.draw(|frame| draw(frame, &mut app))?;
+
+        if event::poll(Duration::from_millis(200))? {
+            if let Event::Key(key) = event::read()? {
+                if key.kind == KeyEventKind::Press && !handle_key(&mut app, key.code) {
+                    return Ok(())
+                }
+            }
+        }
+    }
+}
+
+/// Full screen interface that refreshes the news every 5 minutes, showing the stories `filter` keeps
+pub async fn run(http: HttpClient, user: String, regions: Vec<Region>, filter: NewsFilter) {
+    let (sender, receiver) = mpsc::channel();
+
+    task::spawn(async move {
+        let mut interval = time::interval(Duration::from_secs(60 * 5));
+        let mut last_parsed = newspapers::LastParsed::default();
+
+        loop {
+            interval.tick().await;
+
+            let mut sources = newspapers::get_all(&http, &regions, &mut last_parsed).await;
+            sources.iter_mut().for_each(|(_, news)| news.retain(|journal_new| filter.matches(journal_new)));
+
+            if sender.send(sources).is_err() {
+                break
+            }
+        }
+    });
+
+    let terminal = ratatui::init();
+    let result = task::spawn_blocking(move || ui_loop(terminal, user, receiver)).await;
+    ratatui::restore();
+
+    if let Ok(Err(error)) = result {
+        println!("{}", error)
+    }
+}
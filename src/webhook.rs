@@ -0,0 +1,228 @@
+use std::{io::Write, time::Duration};
+
+use hmac::{Hmac, Mac};
+use serde::{Serialize, Deserialize};
+use sha2::Sha256;
+
+use crate::{authors, model::{ContentKind, JournalNew, Section}};
+
+pub const SIGNATURE_HEADER: &str = "X-Jornais-Signature";
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct WebhookConfig {
+    pub urls: Vec<String>,
+    /// When set every body is signed with HMAC-SHA256 and sent in the `X-Jornais-Signature` header
+    pub secret: Option<String>,
+    pub max_attempts: u32,
+    /// Delay before the first retry, doubled after every failed attempt
+    pub backoff_ms: u64,
+    pub dead_letter_file: String,
+    /// A delivery that gets no answer in this time counts as a failed attempt
+    pub timeout_secs: u64,
+    /// Only notify the stories signed by one of these authors, in any spelling, every story when empty
+    pub authors: Vec<String>
+}
+
+impl Default for WebhookConfig {
+    fn default() -> Self {
+        WebhookConfig {
+            urls: vec![],
+            secret: None,
+            max_attempts: 5,
+            backoff_ms: 1000,
+            dead_letter_file: String::from("webhooks_dead_letter.jsonl"),
+            timeout_secs: 10,
+            authors: vec![]
+        }
+    }
+}
+
+impl WebhookConfig {
+    /// Whether the story should be notified, see `authors`
+    pub fn wants(&self, journal_new: &JournalNew) -> bool {
+        self.authors.is_empty() || authors::signed_by(&journal_new.authors, &self.authors)
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Eq, PartialEq)]
+pub struct WebhookPayload {
+    pub source: String,
+    pub region: String,
+    pub title: String,
+    pub text: String,
+    pub link: Option<String>,
+    #[serde(default)]
+    pub kind: ContentKind,
+    #[serde(default)]
+    pub paywalled: bool,
+    #[serde(default)]
+    pub section: Section,
+    #[serde(default)]
+    pub authors: Vec<String>,
+    pub seen_at: String,
+    pub sent_at: String
+}
+
+impl WebhookPayload {
+    pub fn new(journal_new: &JournalNew, seen_at: chrono::DateTime<chrono::Utc>) -> WebhookPayload {
+        WebhookPayload {
+            source: journal_new.newspaper.to_string(),
+            region: journal_new.newspaper.region().to_string(),
+            title: journal_new.title.clone(),
+            text: journal_new.text.clone(),
+            link: journal_new.link.clone(),
+            kind: journal_new.kind,
+            paywalled: journal_new.paywalled,
+            section: journal_new.section,
+            authors: journal_new.authors.clone(),
+            seen_at: seen_at.to_rfc3339(),
+            sent_at: chrono::Utc::now().to_rfc3339()
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+struct DeadLetter {
+    url: String,
+    attempts: u32,
+    error: String,
+    payload: WebhookPayload
+}
+
+/// Returns the hex encoded HMAC-SHA256 of `body`, prefixed with the algorithm like `sha256=...`
+pub fn sign(secret: &str, body: &str) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any size");
+    mac.update(body.as_bytes());
+
+    format!("sha256={}", hex::encode(mac.finalize().into_bytes()))
+}
+
+async fn deliver(client: &reqwest::Client, config: &WebhookConfig, url: &str, body: &str) -> Result<(), String> {
+    let mut request = client
+    .post(url)
+    .timeout(Duration::from_secs(config.timeout_secs))
+    .header(reqwest::header::CONTENT_TYPE, "application/json")
+    .body(String::from(body));
+
+    if let Some(secret) = &config.secret {
+        request = request.header(SIGNATURE_HEADER, sign(secret, body));
+    }
+
+    match request.send().await {
+        Ok(response) if response.status().is_success() => Ok(()),
+        Ok(response) => Err(format!("Webhook answered with status {}", response.status())),
+        Err(error) => Err(error.to_string())
+    }
+}
+
+fn write_dead_letter(path: &str, dead_letter: &DeadLetter) {
+    let line = serde_json::to_string(dead_letter).expect("Dead letters should be serializable");
+
+    match std::fs::OpenOptions::new().create(true).append(true).open(path) {
+        Ok(mut file) => {
+            if let Err(error) = writeln!(file, "{}", line) {
+                tracing::error!(%error, path, "Couldn't write the dead letter")
+            }
+        },
+        Err(error) => tracing::error!(%error, path, "Couldn't open the dead letter file")
+    }
+}
+
+/// Sends `payload` to every configured URL, retrying with exponential backoff.
+/// Deliveries that still fail after `max_attempts` are appended to the dead letter file.
+pub async fn notify(client: &reqwest::Client, config: &WebhookConfig, payload: &WebhookPayload) {
+    let body = serde_json::to_string(payload).expect("Webhook payloads should be serializable");
+
+    for url in &config.urls {
+        let mut attempts = 0;
+        let mut backoff = Duration::from_millis(config.backoff_ms);
+
+        loop {
+            attempts += 1;
+
+            match deliver(client, config, url, &body).await {
+                Ok(_) => break,
+                Err(error) if attempts >= config.max_attempts => {
+                    tracing::warn!(url, attempts, %error, "Giving up on the webhook delivery");
+                    write_dead_letter(&config.dead_letter_file, &DeadLetter {
+                        url: url.clone(),
+                        attempts,
+                        error,
+                        payload: payload.clone()
+                    });
+                    break
+                },
+                Err(error) => {
+                    tracing::debug!(url, attempts, %error, "Webhook delivery failed, retrying");
+                    tokio::time::sleep(backoff).await;
+                    backoff *= 2;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{sample_new, webhook_receiver};
+
+    #[tokio::test]
+    async fn delivers_signed_payload() {
+        let (address, handle) = webhook_receiver(vec![200]).await;
+        let config = WebhookConfig {
+            urls: vec![address],
+            secret: Some(String::from("secret")),
+            ..Default::default()
+        };
+        let payload = WebhookPayload::new(&sample_new(), chrono::Utc::now());
+
+        notify(&reqwest::Client::new(), &config, &payload).await;
+
+        let received = handle.await.unwrap();
+        let (headers, body) = &received[0];
+
+        assert_eq!(serde_json::from_str::<WebhookPayload>(body).unwrap(), payload);
+        assert!(headers.to_lowercase().contains(&format!("x-jornais-signature: {}", sign("secret", body))));
+    }
+
+    #[tokio::test]
+    async fn retries_then_dead_letters() {
+        let (address, handle) = webhook_receiver(vec![500, 500]).await;
+        let dead_letter_file = std::env::temp_dir().join(format!("jornais_dead_letter_{}.jsonl", std::process::id()));
+        let _ = std::fs::remove_file(&dead_letter_file);
+
+        let config = WebhookConfig {
+            urls: vec![address.clone()],
+            secret: None,
+            max_attempts: 2,
+            backoff_ms: 1,
+            dead_letter_file: dead_letter_file.to_string_lossy().to_string(),
+            timeout_secs: 1,
+            authors: vec![]
+        };
+        let payload = WebhookPayload::new(&sample_new(), chrono::Utc::now());
+
+        notify(&reqwest::Client::new(), &config, &payload).await;
+
+        assert_eq!(handle.await.unwrap().len(), 2);
+
+        let dead_letters = std::fs::read_to_string(&dead_letter_file).unwrap();
+        let _ = std::fs::remove_file(&dead_letter_file);
+
+        assert_eq!(dead_letters.lines().count(), 1);
+        assert!(dead_letters.contains(&address));
+        assert!(dead_letters.contains("\"attempts\":2"));
+    }
+
+    #[test]
+    fn wants_the_configured_authors() {
+        let mut journal_new = sample_new();
+        journal_new.authors = vec![String::from("María Gómez")];
+
+        assert!(WebhookConfig { authors: vec![String::from("maria gomez")], ..Default::default() }.wants(&journal_new));
+        assert!(!WebhookConfig { authors: vec![String::from("Juan Pérez")], ..Default::default() }.wants(&journal_new));
+        assert!(WebhookConfig::default().wants(&journal_new));
+    }
+}